@@ -6,25 +6,39 @@ use std::fs;
 use std::path::PathBuf;
 use test::Bencher;
 
-use temperature::Simulator;
 use temperature::circuit::HotSpot;
+use temperature::{Config, Simulator, Solver};
 
 #[bench] fn next_0001(bench: &mut Bencher) { next(   1, bench); }
 #[bench] fn next_0010(bench: &mut Bencher) { next(  10, bench); }
 #[bench] fn next_0100(bench: &mut Bencher) { next( 100, bench); }
 #[bench] fn next_1000(bench: &mut Bencher) { next(1000, bench); }
 
+#[bench] fn modal_0001(bench: &mut Bencher) { modal(   1, bench); }
+#[bench] fn modal_0010(bench: &mut Bencher) { modal(  10, bench); }
+#[bench] fn modal_0100(bench: &mut Bencher) { modal( 100, bench); }
+#[bench] fn modal_1000(bench: &mut Bencher) { modal(1000, bench); }
+
 fn next(steps: usize, bench: &mut Bencher) {
+    run(steps, Solver::Dense, bench);
+}
+
+fn modal(steps: usize, bench: &mut Bencher) {
+    run(steps, Solver::Modal, bench);
+}
+
+fn run(steps: usize, solver: Solver, bench: &mut Bencher) {
     let units = 32;
-    let mut simulator = setup("032");
+    let mut simulator = setup("032", solver);
     let P = random::default().iter().take(steps * units).collect::<Vec<_>>();
     let mut Q = vec![0.0; steps * units];
     bench.iter(|| simulator.next(&P, &mut Q));
 }
 
-fn setup(name: &str) -> Simulator {
+fn setup(name: &str, solver: Solver) -> Simulator {
     let circuit = HotSpot::new(find(&format!("{}.flp", name)), find("hotspot.config"));
-    Simulator::new(circuit.unwrap(), Default::default()).unwrap()
+    let config = Config { solver: solver, ..Default::default() };
+    Simulator::new(circuit.unwrap(), config).unwrap()
 }
 
 fn find(name: &str) -> PathBuf {
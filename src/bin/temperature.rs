@@ -0,0 +1,218 @@
+#![allow(non_snake_case)]
+
+extern crate matrix;
+extern crate temperature;
+
+use matrix::Size;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::{env, process};
+
+use temperature::trace::HotSpot;
+use temperature::{Circuit, Config, Error, ErrorKind, Result, Simulator};
+
+const USAGE: &'static str = "
+Usage: temperature simulate <circuit> --power <file> [options]
+       temperature steady <circuit> --power <file> [options]
+       temperature inspect <circuit>
+
+Circuits:
+    --hotspot <floorplan> <config>  A HotSpot model.
+    --3d-ice <stack>                A 3D-ICE model.
+
+Options:
+    --power <file>       A power trace in the HotSpot format.
+    --output <file>      A temperature trace to write (standard output if absent).
+    --ambience <kelvin>  The temperature of the ambience.
+    --time-step <secs>   The time step of the simulator.
+    --celsius            Write the temperature in Celsius.
+    --help               Display this message.
+
+The steady command computes the steady state under the power averaged over
+all the time steps of the power trace.
+";
+
+const OPTIONS: &'static [&'static str] = &[
+    "--hotspot", "--3d-ice", "--power", "--output", "--ambience", "--time-step", "--celsius",
+    "--help",
+];
+
+struct Arguments {
+    command: String,
+    options: HashMap<String, Vec<String>>,
+}
+
+fn main() {
+    let arguments = env::args().skip(1).collect::<Vec<_>>();
+    if arguments.is_empty() || arguments.iter().any(|argument| argument == "--help") {
+        println!("{}", USAGE.trim());
+        return;
+    }
+    if let Err(error) = start(arguments) {
+        let _ = writeln!(io::stderr(), "Error: {}.", error);
+        if error.kind() == ErrorKind::InvalidInput {
+            let _ = writeln!(io::stderr(), "{}", USAGE);
+        }
+        process::exit(1);
+    }
+}
+
+fn start(arguments: Vec<String>) -> Result<()> {
+    let arguments = Arguments::parse(arguments)?;
+    match &*arguments.command {
+        "simulate" => simulate(&arguments),
+        "steady" => steady(&arguments),
+        "inspect" => inspect(&arguments),
+        command => raise(format!("the command “{}” is unknown", command)),
+    }
+}
+
+fn simulate(arguments: &Arguments) -> Result<()> {
+    let circuit = arguments.circuit()?;
+    let P = HotSpot::read(arguments.value("--power")?, &circuit.units)?;
+    let steps = P.len() / circuit.units.len();
+    let spots = circuit.spots.clone();
+    let mut simulator = Simulator::new(circuit, arguments.config()?)?;
+    let mut Q = vec![0.0; spots.len() * steps];
+    simulator.try_next(&P, &mut Q)?;
+    arguments.write(&spots, &Q)
+}
+
+fn steady(arguments: &Arguments) -> Result<()> {
+    let circuit = arguments.circuit()?;
+    let units = circuit.units.len();
+    let trace = HotSpot::read(arguments.value("--power")?, &circuit.units)?;
+    let steps = trace.len() / units;
+    if steps == 0 {
+        return raise("the power trace is empty");
+    }
+    let mut P = vec![0.0; units];
+    for (i, &value) in trace.iter().enumerate() {
+        P[i % units] += value / steps as f64;
+    }
+    let spots = circuit.spots.clone();
+    let simulator = Simulator::new(circuit, arguments.config()?)?;
+    let mut Q = vec![0.0; spots.len()];
+    simulator.steady(&P, &mut Q)?;
+    arguments.write(&spots, &Q)
+}
+
+fn inspect(arguments: &Arguments) -> Result<()> {
+    let circuit = arguments.circuit()?;
+    circuit.validate()?;
+    let Circuit { ref capacitance, ref units, ref spots, .. } = circuit;
+    println!("Nodes: {}", capacitance.rows());
+    println!("Processing elements: {}", units.len());
+    for (i, name) in units.iter().enumerate() {
+        println!("    {:>4} {}", i, name);
+    }
+    println!("Spots: {}", spots.len());
+    for (i, name) in spots.iter().enumerate() {
+        println!("    {:>4} {}", i, name);
+    }
+    Ok(())
+}
+
+impl Arguments {
+    fn parse(arguments: Vec<String>) -> Result<Arguments> {
+        let mut arguments = arguments.into_iter();
+        let command = arguments.next().unwrap();
+        let mut options = HashMap::new();
+        let mut name: Option<String> = None;
+        for argument in arguments {
+            if argument.starts_with("--") {
+                if !OPTIONS.contains(&&*argument) {
+                    return raise(format!("the option “{}” is unknown", argument));
+                }
+                options.insert(argument.clone(), vec![]);
+                name = Some(argument);
+                continue;
+            }
+            match name {
+                Some(ref name) => options.get_mut(name).unwrap().push(argument),
+                _ => return raise(format!("the argument “{}” is unexpected", argument)),
+            }
+        }
+        Ok(Arguments { command: command, options: options })
+    }
+
+    fn circuit(&self) -> Result<Circuit> {
+        if let Some(values) = self.options.get("--hotspot") {
+            if values.len() != 2 {
+                return raise("--hotspot expects a floorplan and a configuration file");
+            }
+            return hotspot(&values[0], &values[1]);
+        }
+        if let Some(values) = self.options.get("--3d-ice") {
+            if values.len() != 1 {
+                return raise("--3d-ice expects a stack-description file");
+            }
+            return threed_ice(&values[0]);
+        }
+        raise("a circuit should be given via --hotspot or --3d-ice")
+    }
+
+    fn config(&self) -> Result<Config> {
+        let mut config = Config::default();
+        if self.options.contains_key("--ambience") {
+            config.ambience = self.number("--ambience")?;
+        }
+        if self.options.contains_key("--time-step") {
+            config.time_step = self.number("--time-step")?;
+            if config.time_step <= 0.0 {
+                return raise(format!("--time-step expects a positive number but got {}",
+                                     config.time_step));
+            }
+        }
+        Ok(config)
+    }
+
+    fn write(&self, spots: &[String], Q: &[f64]) -> Result<()> {
+        let celsius = self.options.contains_key("--celsius");
+        if self.options.contains_key("--output") {
+            return HotSpot::write(self.value("--output")?, spots, Q, celsius);
+        }
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        HotSpot::write_to(&mut stdout, spots, Q, celsius)
+    }
+
+    fn number(&self, name: &str) -> Result<f64> {
+        let value = self.value(name)?;
+        match value.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => raise(format!("{} expects a number but got “{}”", name, value)),
+        }
+    }
+
+    fn value(&self, name: &str) -> Result<&str> {
+        match self.options.get(name) {
+            Some(values) if values.len() == 1 => Ok(&values[0]),
+            _ => raise(format!("{} expects a single value", name)),
+        }
+    }
+}
+
+#[cfg(feature = "hotspot")]
+fn hotspot(floorplan: &str, config: &str) -> Result<Circuit> {
+    temperature::circuit::HotSpot::new(floorplan, config)
+}
+
+#[cfg(not(feature = "hotspot"))]
+fn hotspot(_: &str, _: &str) -> Result<Circuit> {
+    Err(Error::new(ErrorKind::Unsupported, "the HotSpot model is not enabled"))
+}
+
+#[cfg(feature = "threed-ice")]
+fn threed_ice(stack: &str) -> Result<Circuit> {
+    temperature::circuit::ThreeDICE::new(stack)
+}
+
+#[cfg(not(feature = "threed-ice"))]
+fn threed_ice(_: &str) -> Result<Circuit> {
+    Err(Error::new(ErrorKind::Unsupported, "the 3D-ICE model is not enabled"))
+}
+
+fn raise<T, M: Into<String>>(message: M) -> Result<T> {
+    Err(Error::new(ErrorKind::InvalidInput, message))
+}
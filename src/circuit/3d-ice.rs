@@ -1,6 +1,7 @@
 extern crate threed_ice;
 
 use matrix::operation::Transpose;
+use matrix::Size;
 use self::threed_ice::{AnalysisType, StackElement, System};
 use std::path::Path;
 
@@ -11,25 +12,64 @@ pub struct ThreeDICE;
 
 impl ThreeDICE {
     /// Construct a thermal circuit given a configuration file.
+    ///
+    /// An error of kind `Io` is returned if the file cannot be accessed, and an
+    /// error of kind `Parse` is returned in case of any other failure of
+    /// 3D-ICE.
     pub fn new<T: AsRef<Path>>(config: T) -> Result<Circuit> {
-        ThreeDICE::from(&ok!(System::new(config)))
+        let config = config.as_ref();
+        match System::new(config) {
+            Ok(system) => ThreeDICE::from(&system),
+            Err(error) => Err(super::backend(error, &[config])),
+        }
     }
 
     /// Construct a thermal circuit given a system.
+    ///
+    /// Any failure of 3D-ICE to extract the matrices is reported as an error of
+    /// kind `Parse`.
     pub fn from(system: &System) -> Result<Circuit> {
         if system.stack.elements.iter().any(|element| element == &StackElement::Channel) {
-            raise!("microchannels are not supported");
+            raise!(Unsupported, "microchannels are not supported");
         }
         if system.analysis.kind() != AnalysisType::Steady {
-            raise!("the analysis type should be set to “steady”");
+            raise!(Unsupported, "the analysis type should be set to “steady”");
         }
-        let distribution = ok!(system.distribution());
+        let distribution = ok!(system.distribution(), Parse);
         let aggregation = distribution.transpose();
+        let names = names(system);
+        if names.len() != distribution.columns() {
+            raise!(Parse, format!("the stack should contain {} processing elements",
+                                  distribution.columns()));
+        }
         Ok(Circuit {
-            capacitance: ok!(system.capacitance()),
-            conductance: ok!(system.conductance()),
+            capacitance: ok!(system.capacitance(), Parse),
+            conductance: ok!(system.conductance(), Parse),
             distribution: distribution,
             aggregation: aggregation,
+            units: names.clone(),
+            spots: names,
         })
     }
 }
+
+fn names(system: &System) -> Vec<String> {
+    let dies = system.stack.elements.iter().rev().filter_map(|element| match *element {
+        StackElement::Die(ref die) => Some(die),
+        _ => None,
+    }).collect::<Vec<_>>();
+    let unique = dies.iter().enumerate().all(|(i, die)| {
+        dies[(i + 1)..].iter().all(|other| other.id != die.id)
+    });
+    let mut names = vec![];
+    for (i, die) in dies.iter().enumerate() {
+        for element in &die.floorplan.elements {
+            names.push(match (dies.len(), unique) {
+                (1, _) => element.id.clone(),
+                (_, true) => format!("{}.{}", die.id, element.id),
+                _ => format!("{}{}.{}", die.id, i, element.id),
+            });
+        }
+    }
+    names
+}
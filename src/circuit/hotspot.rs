@@ -1,6 +1,7 @@
 extern crate hotspot;
 
 use matrix::format::{Compressed, Diagonal};
+use std::fs;
 use std::path::Path;
 
 use {Circuit, Result};
@@ -10,15 +11,40 @@ pub struct HotSpot;
 
 impl HotSpot {
     /// Construct a thermal circuit.
+    ///
+    /// An error of kind `Io` is returned if one of the files cannot be
+    /// accessed, and an error of kind `Parse` is returned in case of any other
+    /// failure of HotSpot.
     pub fn new<F: AsRef<Path>, C: AsRef<Path>>(floorplan: F, config: C) -> Result<Circuit> {
+        let (floorplan, config) = (floorplan.as_ref(), config.as_ref());
         let hotspot::Circuit { units, nodes, capacitance, conductance } = {
-            ok!(hotspot::Circuit::new(floorplan, config))
+            match hotspot::Circuit::new(floorplan, config) {
+                Ok(circuit) => circuit,
+                Err(error) => return Err(super::backend(error, &[floorplan, config])),
+            }
         };
+        let names = HotSpot::units(floorplan)?;
+        if names.len() != units {
+            raise!(Parse, format!("the floorplan should contain {} processing elements", units));
+        }
         Ok(Circuit {
             capacitance: capacitance,
             conductance: conductance,
             distribution: Compressed::from(Diagonal::from_vec((nodes, units), vec![1.0; units])),
             aggregation: Compressed::from(Diagonal::from_vec((units, nodes), vec![1.0; units])),
+            units: names.clone(),
+            spots: names,
         })
     }
+
+    /// Read the names of the processing elements of a floorplan.
+    ///
+    /// The names are returned in the order of the processing elements in the
+    /// constructed circuit.
+    pub fn units<F: AsRef<Path>>(floorplan: F) -> Result<Vec<String>> {
+        let content = ok!(fs::read_to_string(floorplan), Io);
+        Ok(content.lines().map(|line| line.trim()).filter(|line| {
+            !line.is_empty() && !line.starts_with('#')
+        }).filter_map(|line| line.split_whitespace().next()).map(String::from).collect())
+    }
 }
@@ -1,5 +1,17 @@
 //! Thermal circuits.
 
+use matrix::format::{Compressed, Conventional};
+use matrix::Size;
+use std::collections::HashSet;
+
+use {Circuit, Result};
+
+mod modes;
+
+pub use self::modes::Mode;
+
+pub(crate) use self::modes::contributions;
+
 #[cfg(feature = "hotspot")]
 mod hotspot;
 
@@ -12,3 +24,242 @@ mod threed_ice;
 
 #[cfg(feature = "threed-ice")]
 pub use self::threed_ice::ThreeDICE;
+
+impl Circuit {
+    /// Return the index of a processing element given its name.
+    #[inline]
+    pub fn unit(&self, name: &str) -> Option<usize> {
+        self.units.iter().position(|unit| unit == name)
+    }
+
+    /// Return the index of a spot given its name.
+    #[inline]
+    pub fn spot(&self, name: &str) -> Option<usize> {
+        self.spots.iter().position(|spot| spot == name)
+    }
+
+    /// Compute a checksum of the circuit.
+    ///
+    /// The checksum is computed over the dimensions and values of the matrices
+    /// and over the names. It does not depend on the platform.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Hasher::new();
+        hasher.write(&self.capacitance.values);
+        for matrix in &[&self.conductance, &self.distribution, &self.aggregation] {
+            hasher.write_usize(matrix.rows);
+            hasher.write_usize(matrix.columns);
+            hasher.write_usize(matrix.variant as usize);
+            hasher.write(&matrix.values);
+            for &i in matrix.indices.iter().chain(&matrix.offsets) {
+                hasher.write_usize(i);
+            }
+        }
+        for name in self.units.iter().chain(&self.spots) {
+            hasher.write_usize(name.len());
+            hasher.write_bytes(name.as_bytes());
+        }
+        hasher.finish()
+    }
+
+    /// Validate the circuit.
+    ///
+    /// The dimensions of the matrices are checked to agree with each other and,
+    /// unless the names are omitted, with the number of names, the names are
+    /// checked to be unique, the values are checked to be finite, the
+    /// capacitance is checked to be positive, and the conductance matrix is
+    /// checked to be symmetric and positive definite.
+    #[inline]
+    pub fn validate(&self) -> Result<()> {
+        self.check(true)
+    }
+
+    // Validate the circuit. Unless `exhaustive` is set, the dense Cholesky
+    // decomposition is avoided, and the conductance matrix is required to be
+    // diagonally dominant, which guarantees its positive definiteness.
+    pub(crate) fn check(&self, exhaustive: bool) -> Result<()> {
+        let Circuit {
+            ref capacitance, ref conductance, ref distribution, ref aggregation, ..
+        } = *self;
+        let (nodes, spots) = (distribution.rows(), aggregation.rows());
+        for &(name, dimensions, expected) in &[
+            ("capacitance", capacitance.dimensions(), (nodes, nodes)),
+            ("conductance", conductance.dimensions(), (nodes, nodes)),
+            ("aggregation", aggregation.dimensions(), (spots, nodes)),
+        ] {
+            if dimensions != expected {
+                raise!(InvalidCircuit, format!("the {} matrix should be {} × {} but is {} × {}",
+                                               name, expected.0, expected.1,
+                                               dimensions.0, dimensions.1));
+            }
+        }
+        for &(name, names, expected) in &[
+            ("processing elements", &self.units, distribution.columns()),
+            ("spots", &self.spots, spots),
+        ] {
+            if names.is_empty() {
+                continue;
+            }
+            if names.len() != expected {
+                raise!(InvalidCircuit, format!("there should be {} names of {} but there are {}",
+                                               expected, name, names.len()));
+            }
+            let mut seen = HashSet::with_capacity(names.len());
+            for label in names.iter() {
+                if !seen.insert(label) {
+                    raise!(InvalidCircuit, format!("the name “{}” is given twice", label));
+                }
+            }
+        }
+        for (i, &value) in capacitance.iter().enumerate() {
+            if value <= 0.0 || !value.is_finite() {
+                raise!(InvalidCircuit, format!("the capacitance of node {} is not positive ({})",
+                                               i, value));
+            }
+        }
+        for &(name, matrix) in &[
+            ("conductance", conductance),
+            ("distribution", distribution),
+            ("aggregation", aggregation),
+        ] {
+            for (i, j, &value) in matrix.iter() {
+                if !value.is_finite() {
+                    let message = format!("the {} matrix has a non-finite value at ({}, {})",
+                                          name, i, j);
+                    raise!(InvalidCircuit, message);
+                }
+            }
+        }
+        let mut diagonal = vec![0.0; nodes];
+        let mut off = vec![0.0; nodes];
+        for (i, j, &value) in conductance.iter() {
+            if i == j {
+                diagonal[i] += value;
+                continue;
+            }
+            let other = conductance.get((j, i));
+            if (value - other).abs() > 1e-10 * value.abs().max(other.abs()) {
+                let message = format!("the conductance matrix is not symmetric at ({}, {})",
+                                      i, j);
+                raise!(InvalidCircuit, message);
+            }
+            off[i] += value.abs();
+        }
+        for (i, &value) in diagonal.iter().enumerate() {
+            if value <= 0.0 {
+                raise!(InvalidCircuit, format!("the conductance of node {} is not positive ({})",
+                                               i, value));
+            }
+        }
+        if !dominant(conductance, &diagonal, &off) {
+            if !exhaustive {
+                raise!(InvalidCircuit, "the conductance matrix is not diagonally dominant");
+            }
+            if !definite(conductance) {
+                raise!(InvalidCircuit, "the conductance matrix is not positive definite");
+            }
+        }
+        Ok(())
+    }
+}
+
+// Classify a failure of a backend, which reports all problems as input-output
+// errors. The failure is attributed to the files if any of them cannot be
+// accessed after the fact, in which case the message names the file, and to
+// their content otherwise. The error of the backend is kept as the source.
+#[cfg(any(feature = "hotspot", feature = "threed-ice"))]
+fn backend(error: ::std::io::Error, paths: &[&::std::path::Path]) -> ::Error {
+    for path in paths {
+        if let Err(reason) = ::std::fs::metadata(path) {
+            let mut error = ::Error::wrap(::ErrorKind::Io, error);
+            error.message = format!("failed to access “{}” ({})", path.display(), reason);
+            return error;
+        }
+    }
+    ::Error::wrap(::ErrorKind::Parse, error)
+}
+
+// Check if each connected component of the matrix is irreducibly diagonally
+// dominant, which, for a symmetric matrix with a positive diagonal, implies
+// positive definiteness.
+fn dominant(matrix: &Compressed<f64>, diagonal: &[f64], off: &[f64]) -> bool {
+    fn find(roots: &mut [usize], mut i: usize) -> usize {
+        while roots[i] != i {
+            roots[i] = roots[roots[i]];
+            i = roots[i];
+        }
+        i
+    }
+
+    let nodes = diagonal.len();
+    if (0..nodes).any(|i| diagonal[i] < off[i]) {
+        return false;
+    }
+    let mut roots = (0..nodes).collect::<Vec<_>>();
+    for (i, j, _) in matrix.iter() {
+        let (i, j) = (find(&mut roots, i), find(&mut roots, j));
+        roots[i] = j;
+    }
+    let mut strict = vec![false; nodes];
+    for i in 0..nodes {
+        if diagonal[i] - off[i] > 1e-10 * diagonal[i] {
+            let root = find(&mut roots, i);
+            strict[root] = true;
+        }
+    }
+    (0..nodes).all(|i| strict[find(&mut roots, i)])
+}
+
+// Check if the matrix is positive definite by attempting the Cholesky
+// decomposition.
+fn definite(matrix: &Compressed<f64>) -> bool {
+    let mut matrix = Conventional::from(matrix);
+    let nodes = matrix.rows();
+    for j in 0..nodes {
+        let mut sum = matrix[(j, j)];
+        for k in 0..j {
+            sum -= matrix[(j, k)] * matrix[(j, k)];
+        }
+        if sum <= 0.0 || !sum.is_finite() {
+            return false;
+        }
+        let pivot = sum.sqrt();
+        matrix[(j, j)] = pivot;
+        for i in (j + 1)..nodes {
+            let mut sum = matrix[(i, j)];
+            for k in 0..j {
+                sum -= matrix[(i, k)] * matrix[(j, k)];
+            }
+            matrix[(i, j)] = sum / pivot;
+        }
+    }
+    true
+}
+
+struct Hasher(u64);
+
+impl Hasher {
+    fn new() -> Hasher {
+        Hasher(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, values: &[f64]) {
+        self.write_usize(values.len());
+        for &value in values {
+            self.write_bytes(&value.to_bits().to_le_bytes());
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_bytes(&(value as u64).to_le_bytes());
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
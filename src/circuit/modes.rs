@@ -0,0 +1,93 @@
+#![allow(non_snake_case)]
+
+use matrix::decomposition::SymmetricEigen;
+use matrix::format::Conventional;
+use matrix::Size;
+
+use {Circuit, Result};
+
+/// A thermal mode of a circuit.
+///
+/// A mode corresponds to an eigenvector of `A`. Its shape is given in terms of
+/// the temperature of the thermal nodes, `D U[:, i]`, which is normalized so
+/// that the element with the largest magnitude is positive.
+#[derive(Clone, Debug)]
+pub struct Mode {
+    /// The eigenvalue `λi` of `A`.
+    pub eigenvalue: f64,
+    /// The time constant `-1 / λi` in seconds.
+    pub time_constant: f64,
+    /// The coupling of the processing elements into the mode, `Mp^T D U[:, i]`.
+    pub units: Vec<f64>,
+    /// The coupling of the mode into the spots, `Mq D U[:, i]`.
+    pub spots: Vec<f64>,
+    /// The contribution to the temperature of interest in the steady state.
+    ///
+    /// The contribution is the largest change of the temperature of a spot
+    /// that the mode can cause when the power of each processing element is at
+    /// most one Watt in magnitude. It is given in Kelvin per Watt.
+    pub contribution: f64,
+}
+
+impl Circuit {
+    /// Compute the thermal modes.
+    ///
+    /// The modes are sorted by their contribution in the descending order.
+    /// The steady-state temperature of spot `k` due to one Watt dissipated by
+    /// processing element `j` is the sum of `time_constant * spots[k] *
+    /// units[j]` over all modes.
+    pub fn modes(&self) -> Result<Vec<Mode>> {
+        self.validate()?;
+        let Circuit {
+            ref capacitance, ref conductance, ref distribution, ref aggregation, ..
+        } = *self;
+        let ((nodes, units), spots) = (distribution.dimensions(), aggregation.rows());
+        let D = capacitance.iter().map(|&value| (1.0 / value).sqrt()).collect::<Vec<_>>();
+        let mut A = Conventional::from(conductance);
+        for i in 0..nodes {
+            for j in 0..nodes {
+                A[(i, j)] *= -D[i] * D[j];
+            }
+        }
+        let (U, L) = ok!(SymmetricEigen::decompose(&A), Numerical);
+        let mut modes = (0..nodes).map(|k| {
+            let mut shape = (0..nodes).map(|i| D[i] * U[(i, k)]).collect::<Vec<_>>();
+            let peak = shape.iter().fold(0.0, |peak: f64, &value| {
+                if value.abs() > peak.abs() { value } else { peak }
+            });
+            if peak < 0.0 {
+                for value in shape.iter_mut() {
+                    *value = -*value;
+                }
+            }
+            let mut mode = Mode {
+                eigenvalue: L[k],
+                time_constant: -1.0 / L[k],
+                units: vec![0.0; units],
+                spots: vec![0.0; spots],
+                contribution: 0.0,
+            };
+            for (i, j, &value) in distribution.iter() {
+                mode.units[j] += value * shape[i];
+            }
+            for (i, j, &value) in aggregation.iter() {
+                mode.spots[i] += value * shape[j];
+            }
+            let contributions = contributions(&mode.spots, &mode.units, L[k]);
+            mode.contribution = contributions.iter().fold(0.0, |maximum: f64, &value| {
+                maximum.max(value)
+            });
+            mode
+        }).collect::<Vec<_>>();
+        modes.sort_by(|one, other| other.contribution.partial_cmp(&one.contribution).unwrap());
+        Ok(modes)
+    }
+}
+
+// Compute the contribution of a mode to the temperature of each spot in the
+// steady state given the couplings of the mode into the spots and of the
+// processing elements into the mode.
+pub(crate) fn contributions(spots: &[f64], units: &[f64], eigenvalue: f64) -> Vec<f64> {
+    let power = units.iter().fold(0.0, |sum, &value| sum + value.abs()) / eigenvalue.abs();
+    spots.iter().map(|&value| value.abs() * power).collect()
+}
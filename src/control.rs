@@ -0,0 +1,133 @@
+//! Dynamic thermal management.
+//!
+//! A controller observes the temperature of interest after each time step and
+//! decides on the power dissipation of the processing elements for the next
+//! one; see `Simulator::control`.
+
+#![allow(non_snake_case)]
+
+/// A controller.
+pub trait Controller {
+    /// Decide on the power dissipation for the next time step.
+    ///
+    /// `Q` is the temperature of interest at the end of the previous time
+    /// step, and `P` contains the power dissipation decided on at the previous
+    /// time step, which should be overwritten with the new decision.
+    fn control(&mut self, Q: &[f64], P: &mut [f64]);
+}
+
+/// A log of a controlled simulation.
+#[derive(Clone, Debug, Default)]
+pub struct Log {
+    /// The power dissipation decided on at each time step.
+    pub power: Vec<f64>,
+    /// The temperature of interest at the end of each time step.
+    pub temperature: Vec<f64>,
+}
+
+/// A threshold controller switching between performance levels.
+///
+/// The levels are ordered from the highest to the lowest performance, and
+/// each level is given by a vector of the power dissipation of the processing
+/// elements, which makes the controller suitable for modeling dynamic voltage
+/// and frequency scaling. When the maximal temperature exceeds `upper`, the
+/// controller moves one level down; when it falls below `lower`, the
+/// controller moves one level up.
+#[derive(Clone, Debug)]
+pub struct Threshold {
+    /// The power dissipation at each level.
+    pub levels: Vec<Vec<f64>>,
+    /// The temperature below which the performance is increased in Kelvin.
+    pub lower: f64,
+    /// The temperature above which the performance is decreased in Kelvin.
+    pub upper: f64,
+    /// The current level.
+    pub level: usize,
+}
+
+/// A proportional-integral-derivative controller.
+///
+/// The controller scales the nominal power dissipation in order to keep the
+/// maximal temperature at the target. The scaling factor is `1 - u` bounded to
+/// `[0, 1]` where `u` is the output of the controller driven by the difference
+/// between the maximal temperature and the target.
+#[derive(Clone, Debug)]
+pub struct PID {
+    /// The nominal power dissipation.
+    pub nominal: Vec<f64>,
+    /// The target temperature in Kelvin.
+    pub target: f64,
+    /// The proportional gain.
+    pub proportional: f64,
+    /// The integral gain.
+    pub integral: f64,
+    /// The derivative gain.
+    pub derivative: f64,
+    /// The time step in seconds.
+    pub time_step: f64,
+    accumulated: f64,
+    previous: Option<f64>,
+}
+
+impl Threshold {
+    /// Create a controller.
+    pub fn new(levels: Vec<Vec<f64>>, lower: f64, upper: f64) -> Threshold {
+        debug_assert!(!levels.is_empty() && lower <= upper);
+        Threshold { levels: levels, lower: lower, upper: upper, level: 0 }
+    }
+}
+
+impl Controller for Threshold {
+    fn control(&mut self, Q: &[f64], P: &mut [f64]) {
+        let maximum = maximum(Q);
+        if maximum > self.upper && self.level + 1 < self.levels.len() {
+            self.level += 1;
+        } else if maximum < self.lower && self.level > 0 {
+            self.level -= 1;
+        }
+        P.copy_from_slice(&self.levels[self.level]);
+    }
+}
+
+impl PID {
+    /// Create a controller.
+    pub fn new(nominal: Vec<f64>, target: f64, proportional: f64, integral: f64,
+               derivative: f64, time_step: f64) -> PID {
+        PID {
+            nominal: nominal,
+            target: target,
+            proportional: proportional,
+            integral: integral,
+            derivative: derivative,
+            time_step: time_step,
+            accumulated: 0.0,
+            previous: None,
+        }
+    }
+}
+
+impl Controller for PID {
+    fn control(&mut self, Q: &[f64], P: &mut [f64]) {
+        let error = maximum(Q) - self.target;
+        let change = match self.previous {
+            Some(previous) => (error - previous) / self.time_step,
+            _ => 0.0,
+        };
+        self.previous = Some(error);
+        let accumulated = self.accumulated + error * self.time_step;
+        let output = self.proportional * error + self.integral * accumulated +
+                     self.derivative * change;
+        let scale = 1.0 - output;
+        if scale > 0.0 && scale < 1.0 {
+            self.accumulated = accumulated;
+        }
+        let scale = scale.max(0.0).min(1.0);
+        for (value, &nominal) in P.iter_mut().zip(&self.nominal) {
+            *value = scale * nominal;
+        }
+    }
+}
+
+fn maximum(Q: &[f64]) -> f64 {
+    Q.iter().fold(::std::f64::NEG_INFINITY, |maximum, &value| maximum.max(value))
+}
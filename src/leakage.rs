@@ -0,0 +1,73 @@
+//! Leakage power.
+//!
+//! The leakage power of a processing element grows with its temperature,
+//! which, in turn, grows with the power dissipated by the element. The models
+//! given here are used by the simulator in order to account for this feedback
+//! loop; see `Simulator::next_leaky`.
+//!
+//! The built-in models assume that the `i`th element of the temperature of
+//! interest corresponds to the `i`th processing element, which is the case for
+//! the circuits constructed by `circuit::HotSpot` and `circuit::ThreeDICE`.
+
+#![allow(non_snake_case)]
+
+/// A leakage model.
+pub trait Leakage {
+    /// Compute the leakage power.
+    ///
+    /// `Q` is the current temperature of interest. The leakage power of the
+    /// processing elements should be added to `P`.
+    fn compute(&self, Q: &[f64], P: &mut [f64]);
+}
+
+/// A leakage model linear in temperature.
+///
+/// The leakage power of the `i`th processing element is given by
+/// `nominal[i] * (1 + coefficient * (Q[i] - reference))` and is bounded from
+/// below by zero.
+#[derive(Clone, Debug)]
+pub struct Linear {
+    /// The leakage power at the reference temperature in watts.
+    pub nominal: Vec<f64>,
+    /// The reference temperature in Kelvin.
+    pub reference: f64,
+    /// The relative change of the leakage power per Kelvin.
+    pub coefficient: f64,
+}
+
+/// A leakage model exponential in temperature.
+///
+/// The leakage power of the `i`th processing element is given by
+/// `nominal[i] * exp(coefficient * (Q[i] - reference))`.
+#[derive(Clone, Debug)]
+pub struct Exponential {
+    /// The leakage power at the reference temperature in watts.
+    pub nominal: Vec<f64>,
+    /// The reference temperature in Kelvin.
+    pub reference: f64,
+    /// The exponent of the growth of the leakage power per Kelvin.
+    pub coefficient: f64,
+}
+
+impl Leakage for Linear {
+    fn compute(&self, Q: &[f64], P: &mut [f64]) {
+        let Linear { ref nominal, reference, coefficient } = *self;
+        debug_assert_eq!(nominal.len(), P.len());
+        for (i, value) in P.iter_mut().enumerate() {
+            let factor = 1.0 + coefficient * (Q[i] - reference);
+            if factor > 0.0 {
+                *value += nominal[i] * factor;
+            }
+        }
+    }
+}
+
+impl Leakage for Exponential {
+    fn compute(&self, Q: &[f64], P: &mut [f64]) {
+        let Exponential { ref nominal, reference, coefficient } = *self;
+        debug_assert_eq!(nominal.len(), P.len());
+        for (i, value) in P.iter_mut().enumerate() {
+            *value += nominal[i] * (coefficient * (Q[i] - reference)).exp();
+        }
+    }
+}
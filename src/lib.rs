@@ -87,67 +87,207 @@
 //! for the whole time span of interest, the time span is split into small
 //! subintervals, and the above equation is successively applied to each of
 //! them.
+//!
+//! In the steady state, `dS/dt = 0`, and the solution is
+//!
+//! ```math
+//! S = -A^(-1) B P = U diag(-1 / λi) U^T B P,
+//! ```
+//!
+//! which is obtained using the same eigendecomposition.
+//!
+//! For large circuits, the eigendecomposition can be avoided by choosing
+//! `Solver::Sparse`, in which case `A` is kept sparse, and the solution is
+//! obtained using the equivalent formulation
+//!
+//! ```math
+//! S(t) = S(0) + Δt φ1(A Δt) (A S(0) + B P(0))
+//! ```
+//!
+//! where `φ1(z) = (exp(z) - 1) / z`. The action of `φ1(A Δt)` on a vector is
+//! approximated in a Krylov subspace constructed using the Lanczos process.
 
 #[cfg(test)]
 extern crate assert;
 extern crate matrix;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+use std::sync::Arc;
 use std::{default, error, fmt, result};
 use matrix::format::{Compressed, Diagonal};
 
 /// A thermal circuit.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Circuit {
     /// The thermal-capacitance matrix.
+    #[cfg_attr(feature = "serde", serde(with = "serialization::diagonal"))]
     pub capacitance: Diagonal<f64>,
     /// The thermal-conductance matrix.
+    #[cfg_attr(feature = "serde", serde(with = "serialization::compressed"))]
     pub conductance: Compressed<f64>,
     /// The power-distribution matrix.
+    #[cfg_attr(feature = "serde", serde(with = "serialization::compressed"))]
     pub distribution: Compressed<f64>,
     /// The temperature-aggregation matrix.
+    #[cfg_attr(feature = "serde", serde(with = "serialization::compressed"))]
     pub aggregation: Compressed<f64>,
+    /// The names of the processing elements, which can be empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub units: Vec<String>,
+    /// The names of the spots, that is, of the elements of the temperature of
+    /// interest, which can be empty.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spots: Vec<String>,
 }
 
 /// A configuration of temperature simulation.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Config {
     /// The temperature of the ambience in Kelvin.
     pub ambience: f64,
     /// The time step of the simulator in seconds.
     pub time_step: f64,
+    /// The solver.
+    pub solver: Solver,
+}
+
+/// A solver of the thermal system.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Solver {
+    /// The solver that computes the eigendecomposition of the system and
+    /// advances the state using dense matrices.
+    ///
+    /// The memory and time required for the setup grow quadratically and
+    /// cubically, respectively, with the number of thermal nodes.
+    Dense,
+    /// The solver that computes the eigendecomposition of the system and
+    /// advances the state in the eigenbasis.
+    ///
+    /// Each time step requires a multiplication by a diagonal matrix instead of
+    /// a dense one, and the temperature of the thermal nodes is computed only
+    /// on demand.
+    Modal,
+    /// The solver that keeps the system sparse and advances the state using a
+    /// Krylov approximation of the action of the matrix exponential.
+    ///
+    /// The approximation is refined until the relative change of the result
+    /// falls below `tolerance`, and an error is reported if the refinement
+    /// fails. The steady state is computed using the conjugate-gradient method
+    /// with the same tolerance, and an error is returned if the method fails to
+    /// converge. Since the dense Cholesky decomposition is avoided, the
+    /// conductance matrix is required to be diagonally dominant, and circuits
+    /// whose conductance matrix is not are rejected.
+    Sparse {
+        /// The relative tolerance.
+        tolerance: f64,
+    },
+    /// The solver that computes the eigendecomposition of the system and
+    /// advances the state using only the dominant modes.
+    ///
+    /// The modes are ranked by their contribution to the temperature of
+    /// interest in the steady state. The discarded modes are residualized,
+    /// that is, assumed to settle instantaneously, so that the steady state is
+    /// preserved. The resulting error bound is given by `System::bound`.
+    ///
+    /// All the time-domain computations use the reduced model. The Foster
+    /// networks and the transfer function are computed using all the modes.
+    Reduced(Reduction),
+}
+
+/// A reduction of the number of modes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Reduction {
+    /// Keep a fixed number of modes.
+    Modes(usize),
+    /// Keep as few modes as possible while keeping the error bound, which is
+    /// given in Kelvin per Watt, below a threshold.
+    Tolerance(f64),
 }
 
 /// An error.
 #[derive(Clone, Debug)]
-pub struct Error(String);
+pub struct Error {
+    kind: ErrorKind,
+    message: String,
+    source: Option<Arc<dyn error::Error + Send + Sync>>,
+}
+
+/// A kind of errors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    /// An input-output error.
+    Io,
+    /// A parsing error.
+    Parse,
+    /// A feature of a model that is not supported.
+    Unsupported,
+    /// An invalid circuit.
+    InvalidCircuit,
+    /// A numerical failure.
+    Numerical,
+    /// A thermal runaway.
+    Runaway,
+    /// An invalid input.
+    InvalidInput,
+}
 
 /// A result.
 pub type Result<T> = result::Result<T, Error>;
 
 macro_rules! raise(
-    ($message:expr) => (
-        return Err(::Error($message.to_string()));
+    ($kind:ident, $message:expr) => (
+        return Err(::Error::new(::ErrorKind::$kind, $message))
     );
 );
 
 macro_rules! ok(
-    ($result:expr) => (
+    ($result:expr, $kind:ident) => (
         match $result {
             Ok(result) => result,
-            Err(error) => raise!(error),
+            Err(error) => return Err(::Error::wrap(::ErrorKind::$kind, error)),
         }
     );
 );
 
+impl Error {
+    /// Create an error.
+    pub fn new<T: Into<String>>(kind: ErrorKind, message: T) -> Error {
+        Error { kind: kind, message: message.into(), source: None }
+    }
+
+    /// Create an error caused by another error.
+    pub fn wrap<T>(kind: ErrorKind, error: T) -> Error
+        where T: error::Error + Send + Sync + 'static
+    {
+        Error { kind: kind, message: error.to_string(), source: Some(Arc::new(error)) }
+    }
+
+    /// Return the kind.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        self.0.fmt(formatter)
+        self.message.fmt(formatter)
     }
 }
 
 impl error::Error for Error {
-    fn description(&self) -> &str {
-        &self.0
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.source {
+            Some(ref error) => Some(&**error),
+            _ => None,
+        }
     }
 }
 
@@ -157,11 +297,19 @@ impl default::Default for Config {
         Config {
             ambience: 318.15,
             time_step: 1e-3,
+            solver: Solver::Dense,
         }
     }
 }
 
 mod simulator;
 pub mod circuit;
+pub mod control;
+pub mod leakage;
+pub mod network;
+pub mod trace;
+
+#[cfg(feature = "serde")]
+pub mod serialization;
 
-pub use simulator::Simulator;
+pub use simulator::{Simulator, Snapshot, System};
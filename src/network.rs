@@ -0,0 +1,188 @@
+//! Equivalent RC networks.
+//!
+//! The thermal impedance `Zth(t)` between a processing element and a spot is
+//! the rise of the temperature of the spot at time `t` after one Watt starts
+//! being dissipated by the processing element. It can be represented by a
+//! Foster network, which is a series of parallel RC stages:
+//!
+//! ```math
+//! Zth(t) = Σi Ri (1 - exp(-t / (Ri Ci))),
+//! ```
+//!
+//! or by a Cauer network, which is a ladder where the `i`th capacitor connects
+//! the `i`th node to the ambience and the `i`th resistor connects the `i`th
+//! node to the next one, the last resistor connecting the last node to the
+//! ambience. The power enters at the first node.
+
+#![allow(non_snake_case)]
+
+use matrix::decomposition::SymmetricEigen;
+use matrix::format::Conventional;
+use matrix::Matrix;
+use std::fmt;
+
+use Result;
+
+/// An RC stage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stage {
+    /// The thermal resistance in Kelvin per Watt.
+    pub resistance: f64,
+    /// The thermal capacitance in Joules per Kelvin.
+    pub capacitance: f64,
+}
+
+/// A Foster network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Foster {
+    /// The stages.
+    pub stages: Vec<Stage>,
+}
+
+/// A Cauer network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cauer {
+    /// The stages starting from the node where the power enters.
+    pub stages: Vec<Stage>,
+}
+
+impl Stage {
+    /// Return the time constant in seconds.
+    #[inline]
+    pub fn time_constant(&self) -> f64 {
+        self.resistance * self.capacitance
+    }
+}
+
+impl Foster {
+    /// Compute the thermal impedance at a time point.
+    pub fn impedance(&self, time: f64) -> f64 {
+        self.stages.iter().fold(0.0, |sum, stage| {
+            sum - stage.resistance * (-time / stage.time_constant()).exp_m1()
+        })
+    }
+
+    /// Convert the network into a Cauer network.
+    ///
+    /// The conversion requires the resistances and capacitances to be
+    /// positive, which is always the case for the impedance between a
+    /// processing element and a spot aggregating the same thermal nodes. It
+    /// is performed using the Lanczos process. Stages whose contribution is
+    /// below the precision of the process are discarded, and, therefore, the
+    /// Cauer network might have fewer stages.
+    pub fn cauer(&self) -> Result<Cauer> {
+        let count = self.stages.len();
+        if count == 0 {
+            return Ok(Cauer { stages: vec![] });
+        }
+        for stage in &self.stages {
+            if stage.resistance <= 0.0 || stage.capacitance <= 0.0 ||
+               !stage.time_constant().is_finite() {
+                raise!(InvalidInput, "the resistances and capacitances should be positive");
+            }
+        }
+        let rates = self.stages.iter().map(|stage| 1.0 / stage.time_constant());
+        let rates = rates.collect::<Vec<_>>();
+        let largest = rates.iter().fold(0.0, |largest: f64, &rate| largest.max(rate));
+        let total = self.stages.iter().fold(0.0, |sum, stage| sum + 1.0 / stage.capacitance);
+        let start = self.stages.iter().map(|stage| (1.0 / (stage.capacitance * total)).sqrt());
+        let mut V = vec![start.collect::<Vec<_>>()];
+        let (mut alphas, mut betas) = (vec![], vec![]);
+        loop {
+            let j = V.len() - 1;
+            let mut u = V[j].iter().zip(&rates).map(|(&v, &rate)| v * rate).collect::<Vec<_>>();
+            let alpha = dot(&V[j], &u);
+            alphas.push(alpha);
+            for v in &V {
+                let projection = dot(v, &u);
+                for i in 0..count {
+                    u[i] -= projection * v[i];
+                }
+            }
+            let beta = dot(&u, &u).sqrt();
+            if V.len() == count || beta <= 1e-12 * largest {
+                break;
+            }
+            betas.push(beta);
+            V.push(u.iter().map(|&value| value / beta).collect());
+        }
+        let mut stages = Vec::with_capacity(alphas.len());
+        let (mut capacitance, mut conductance) = (1.0 / total, 0.0);
+        for (k, &alpha) in alphas.iter().enumerate() {
+            let next = alpha * capacitance - conductance;
+            if next <= 0.0 || !next.is_finite() {
+                raise!(Numerical, "failed to convert the Foster network into a Cauer network");
+            }
+            stages.push(Stage { resistance: 1.0 / next, capacitance: capacitance });
+            if let Some(&beta) = betas.get(k) {
+                capacitance = next * next / (beta * beta * capacitance);
+            }
+            conductance = next;
+        }
+        Ok(Cauer { stages: stages })
+    }
+}
+
+impl Cauer {
+    /// Convert the network into a Foster network.
+    pub fn foster(&self) -> Result<Foster> {
+        let count = self.stages.len();
+        if count == 0 {
+            return Ok(Foster { stages: vec![] });
+        }
+        for stage in &self.stages {
+            if stage.resistance <= 0.0 || stage.capacitance <= 0.0 ||
+               !stage.time_constant().is_finite() {
+                raise!(InvalidInput, "the resistances and capacitances should be positive");
+            }
+        }
+        let mut X = Conventional::zero(count);
+        for (k, stage) in self.stages.iter().enumerate() {
+            let conductance = 1.0 / stage.resistance;
+            X[(k, k)] += conductance / stage.capacitance;
+            if k + 1 < count {
+                let other = self.stages[k + 1].capacitance;
+                X[(k + 1, k + 1)] += conductance / other;
+                let value = -conductance / (stage.capacitance * other).sqrt();
+                X[(k, k + 1)] = value;
+                X[(k + 1, k)] = value;
+            }
+        }
+        let (U, L) = ok!(SymmetricEigen::decompose(&X), Numerical);
+        let mut stages = (0..count).map(|i| {
+            let weight = U[(0, i)] * U[(0, i)] / self.stages[0].capacitance;
+            Stage { resistance: weight / L[i], capacitance: 1.0 / weight }
+        }).collect::<Vec<_>>();
+        stages.sort_by(|one, other| {
+            one.time_constant().partial_cmp(&other.time_constant()).unwrap()
+        });
+        Ok(Foster { stages: stages })
+    }
+}
+
+impl fmt::Display for Foster {
+    /// Format the network as a tab-separated table.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        table(&self.stages, formatter)
+    }
+}
+
+impl fmt::Display for Cauer {
+    /// Format the network as a tab-separated table.
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        table(&self.stages, formatter)
+    }
+}
+
+fn table(stages: &[Stage], formatter: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(formatter, "stage\tresistance\tcapacitance\ttime constant")?;
+    for (i, stage) in stages.iter().enumerate() {
+        writeln!(formatter, "{}\t{:e}\t{:e}\t{:e}", i + 1, stage.resistance, stage.capacitance,
+                 stage.time_constant())?;
+    }
+    Ok(())
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).fold(0.0, |sum, (&x, &y)| sum + x * y)
+}
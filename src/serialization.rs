@@ -0,0 +1,96 @@
+//! Serialization of matrices.
+//!
+//! The functions are to be used via `#[serde(with = "...")]` on fields holding
+//! matrices. Deserialized matrices are checked to be structurally consistent.
+
+use matrix::format::compressed::Variant;
+use matrix::format::{Compressed, Diagonal};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::result;
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Diagonal<f64>")]
+struct DiagonalDef {
+    rows: usize,
+    columns: usize,
+    values: Vec<f64>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Compressed<f64>")]
+struct CompressedDef {
+    rows: usize,
+    columns: usize,
+    nonzeros: usize,
+    #[serde(with = "VariantDef")]
+    variant: Variant,
+    values: Vec<f64>,
+    indices: Vec<usize>,
+    offsets: Vec<usize>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(remote = "Variant")]
+enum VariantDef {
+    Column,
+    Row,
+}
+
+/// Diagonal matrices.
+pub mod diagonal {
+    use super::*;
+
+    /// Serialize a matrix.
+    pub fn serialize<S>(matrix: &Diagonal<f64>, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        DiagonalDef::serialize(matrix, serializer)
+    }
+
+    /// Deserialize a matrix.
+    pub fn deserialize<'l, D>(deserializer: D) -> result::Result<Diagonal<f64>, D::Error>
+        where D: Deserializer<'l>
+    {
+        let matrix = DiagonalDef::deserialize(deserializer)?;
+        if matrix.values.len() != matrix.rows.min(matrix.columns) {
+            return Err(D::Error::custom("the number of diagonal elements is inconsistent"));
+        }
+        Ok(matrix)
+    }
+}
+
+/// Compressed matrices.
+pub mod compressed {
+    use super::*;
+
+    /// Serialize a matrix.
+    pub fn serialize<S>(matrix: &Compressed<f64>, serializer: S) -> result::Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        CompressedDef::serialize(matrix, serializer)
+    }
+
+    /// Deserialize a matrix.
+    pub fn deserialize<'l, D>(deserializer: D) -> result::Result<Compressed<f64>, D::Error>
+        where D: Deserializer<'l>
+    {
+        let matrix = CompressedDef::deserialize(deserializer)?;
+        let (major, minor) = match matrix.variant {
+            Variant::Column => (matrix.columns, matrix.rows),
+            Variant::Row => (matrix.rows, matrix.columns),
+        };
+        let Compressed { nonzeros, ref values, ref indices, ref offsets, .. } = matrix;
+        if values.len() != nonzeros || indices.len() != nonzeros {
+            return Err(D::Error::custom("the number of nonzero elements is inconsistent"));
+        }
+        if offsets.len() != major + 1 || offsets[0] != 0 || offsets[major] != nonzeros ||
+           offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(D::Error::custom("the offsets are inconsistent"));
+        }
+        if indices.iter().any(|&i| i >= minor) {
+            return Err(D::Error::custom("the indices are out of bounds"));
+        }
+        Ok(matrix)
+    }
+}
@@ -1,41 +1,683 @@
 #![allow(non_snake_case)]
 
 use matrix::decomposition::SymmetricEigen;
-use matrix::format::{Compressed, Conventional};
+use matrix::format::{Compressed, Conventional, Diagonal};
 use matrix::operation::{Multiply, MultiplyInto};
-use matrix::{Matrix, Size};
+use matrix::{c64, Matrix, Size};
+use std::collections::HashMap;
+use std::f64::consts::PI;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use std::{mem, ptr};
 
-use {Circuit, Config, Result};
+use control::{Controller, Log};
+use leakage::Leakage;
+use network::{Foster, Stage};
+use {Circuit, Config, Result, Solver};
+
+use self::modal::Modal;
+use self::reduced::Reduced;
+use self::sparse::Sparse;
+
+mod modal;
+mod reduced;
+mod sparse;
+mod storage;
 
 #[cfg(test)]
 mod tests;
 
 /// A temperature simulator.
+#[derive(Clone)]
 pub struct Simulator {
     config: Config,
-    system: System,
+    system: Arc<System>,
+    propagator: Option<Arc<Propagator>>,
+    propagators: HashMap<u64, Arc<Propagator>>,
+    factors: Arc<Factors>,
+    ambience: Vec<f64>,
+    offset: Vec<f64>,
+    state: State,
+    steps: usize,
+}
+
+/// A snapshot of the state of a simulator.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    average: f64,
+    ambience: Vec<f64>,
+    offset: Vec<f64>,
+    state: Vec<f64>,
+    steps: usize,
 }
 
-struct System {
+/// A system of precomputed matrices.
+///
+/// A system can be shared by several simulators, which is useful for
+/// simulating the same circuit under different workloads in parallel.
+pub struct System {
+    config: Config,
     units: usize,
     nodes: usize,
     spots: usize,
     C: Compressed<f64>,
+    D: Diagonal<f64>,
+    Mq: Compressed<f64>,
+    unit_names: Vec<String>,
+    spot_names: Vec<String>,
+    checksum: u64,
+    model: Model,
+}
+
+enum Model {
+    Dense(Dense, Arc<Propagator>),
+    Modal(Dense, Modal),
+    Reduced(Dense, Reduced),
+    Sparse(Sparse),
+}
+
+struct Dense {
+    G: Conventional<f64>,
+    H: Conventional<f64>,
+    L: Diagonal<f64>,
+    U: Conventional<f64>,
+    Z: Conventional<f64>,
+}
+
+struct Propagator {
+    time_step: f64,
     E: Conventional<f64>,
     F: Conventional<f64>,
-    S: State,
 }
 
+struct Factors {
+    time_step: f64,
+    exponent: Vec<f64>,
+    scale: Vec<f64>,
+}
+
+#[derive(Clone)]
 struct State(Vec<f64>);
 
 impl Simulator {
     /// Create a simulator.
     pub fn new(circuit: Circuit, config: Config) -> Result<Simulator> {
-        let Circuit { capacitance, conductance, distribution, aggregation } = circuit;
+        Ok(Simulator::from(Arc::new(System::new(circuit, config)?)))
+    }
+
+    /// Perform the simulation.
+    ///
+    /// The function panics if `Solver::Sparse` fails to reach its tolerance,
+    /// which can be handled using `try_next` instead.
+    pub fn next(&mut self, P: &[f64], Q: &mut [f64]) {
+        if let Err(error) = self.step(P, Q) {
+            panic!("failed to perform the simulation: {}", error);
+        }
+    }
+
+    /// Perform the simulation with checked inputs.
+    ///
+    /// The function is the same as `next` except that the dimensions of `P`
+    /// and `Q` are checked to agree with each other and with the system and
+    /// the power values are checked to be finite. In case of a problem, an
+    /// error specifying the offending sample is returned, and the state of the
+    /// simulator is not altered. The same applies when `Solver::Sparse` fails
+    /// to reach its tolerance.
+    pub fn try_next(&mut self, P: &[f64], Q: &mut [f64]) -> Result<()> {
+        let System { units, spots, .. } = *self.system;
+        if units == 0 || P.len() % units != 0 {
+            raise!(InvalidInput, format!("the number of power values ({}) should be a multiple \
+                                          of the number of processing elements ({})",
+                                         P.len(), units));
+        }
+        let steps = P.len() / units;
+        if Q.len() != spots * steps {
+            raise!(InvalidInput, format!("the number of temperature values ({}) should be {} \
+                                          for {} time steps", Q.len(), spots * steps, steps));
+        }
+        for (k, &value) in P.iter().enumerate() {
+            if !value.is_finite() {
+                raise!(InvalidInput, format!("the power of processing element {} at time step {} \
+                                              is not finite ({})", k % units, k / units, value));
+            }
+        }
+        self.step(P, Q)
+    }
+
+    /// Perform the simulation under the supervision of a controller.
+    ///
+    /// At each of the `steps` time steps, the controller is given the current
+    /// temperature of interest and decides on the power dissipation for the
+    /// time step. The decisions and the resulting temperature are recorded in
+    /// the returned log.
+    pub fn control<T: Controller>(&mut self, controller: &mut T, steps: usize) -> Log {
+        let System { units, spots, .. } = *self.system;
+        let mut log = Log {
+            power: Vec::with_capacity(units * steps),
+            temperature: Vec::with_capacity(spots * steps),
+        };
+        let (mut P, mut Q) = (vec![0.0; units], vec![0.0; spots]);
+        self.observe(&mut Q);
+        for _ in 0..steps {
+            controller.control(&Q, &mut P);
+            self.next(&P, &mut Q);
+            log.power.extend_from_slice(&P);
+            log.temperature.extend_from_slice(&Q);
+        }
+        log
+    }
+
+    /// Perform the simulation with temperature-dependent leakage power.
+    ///
+    /// The leakage power is computed at the beginning of each time step using
+    /// the temperature at that moment and is added to the dynamic power given
+    /// in `P`. If the temperature exceeds `limit` or ceases to be finite, the
+    /// simulation is stopped, and an error reporting a thermal runaway is
+    /// returned.
+    pub fn next_leaky<T: Leakage>(&mut self, P: &[f64], leakage: &T, limit: f64,
+                                  Q: &mut [f64]) -> Result<()> {
+        let System { units, spots, .. } = *self.system;
+        let steps = P.len() / units;
+        debug_assert_eq!(P.len(), units * steps);
+        debug_assert_eq!(Q.len(), spots * steps);
+        let mut current = vec![0.0; spots];
+        self.observe(&mut current);
+        for i in 0..steps {
+            let mut power = P[(i * units)..((i + 1) * units)].to_vec();
+            leakage.compute(&current, &mut power);
+            self.step(&power, &mut current)?;
+            if current.iter().any(|&value| !value.is_finite() || value > limit) {
+                raise!(Runaway, format!("encountered a thermal runaway at step {}", i));
+            }
+            Q[(i * spots)..((i + 1) * spots)].copy_from_slice(&current);
+        }
+        Ok(())
+    }
+
+    /// Perform the simulation with power samples of varying durations.
+    ///
+    /// The `i`th power vector in `P` is assumed to be dissipated for
+    /// `durations[i]` seconds, which should be positive. With `Solver::Dense`,
+    /// the matrices corresponding to each distinct duration are computed once
+    /// and cached in the same way as by `set_time_step`. With the other
+    /// solvers, the diagonal factors corresponding to each distinct duration
+    /// are computed once per call. Like `next`, the function panics if
+    /// `Solver::Sparse` fails to reach its tolerance.
+    pub fn next_variable(&mut self, P: &[f64], durations: &[f64], Q: &mut [f64]) {
+        let System { units, nodes, spots, .. } = *self.system;
+        let steps = durations.len();
+        debug_assert_eq!(P.len(), units * steps);
+        debug_assert_eq!(Q.len(), spots * steps);
+        for &duration in durations {
+            assert!(duration > 0.0, "the durations should be positive but got {}", duration);
+        }
+        let mut cache = HashMap::new();
+        self.state.next(nodes, steps);
+        for i in 0..steps {
+            let (duration, P) = (durations[i], &P[(i * units)..((i + 1) * units)]);
+            let propagator = self.propagator(duration);
+            let (from, into) = self.state[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
+            if let Some(propagator) = propagator {
+                propagator.F.multiply_into(P, into);
+                propagator.E.multiply_into(from, into);
+                continue;
+            }
+            let system = &self.system;
+            let factors = cache.entry(duration.to_bits()).or_insert_with(|| {
+                Factors::new(system.eigenvalues(), duration)
+            });
+            if let Err(error) = system.advance(factors, from, P, into) {
+                panic!("failed to perform the simulation: {}", error);
+            }
+        }
+        fill(Q, &self.offset);
+        self.system.output(&self.state[nodes..], Q);
+        self.steps += steps;
+    }
+
+    /// Evaluate the temperature at arbitrary time points.
+    ///
+    /// The time points are measured in seconds from the current moment and
+    /// can be arbitrary, including those that do not fall on the boundaries of
+    /// time steps. The power dissipation is assumed to be constant and equal
+    /// to `P`. The state of the simulator is not altered. An error is
+    /// returned if `Solver::Sparse` fails to reach its tolerance.
+    pub fn evaluate(&self, P: &[f64], times: &[f64], Q: &mut [f64]) -> Result<()> {
+        let System { units, nodes, spots, .. } = *self.system;
+        debug_assert_eq!(P.len(), units);
+        debug_assert_eq!(Q.len(), spots * times.len());
+        let mut S = vec![0.0; nodes];
+        for (i, &time) in times.iter().enumerate() {
+            debug_assert!(time >= 0.0);
+            for value in S.iter_mut() {
+                *value = 0.0;
+            }
+            self.system.propagate(time, self.state.current(nodes), P, &mut S)?;
+            let Q = &mut Q[(i * spots)..((i + 1) * spots)];
+            fill(Q, &self.offset);
+            self.system.output(&S, Q);
+        }
+        Ok(())
+    }
+
+    /// Perform the simulation with a time-varying ambience.
+    ///
+    /// The ambient temperature is assumed to be constant within each time
+    /// step. `Tamb` contains either one value per time step, in which case the
+    /// ambience is uniform, or `nodes` values per time step. The layout is
+    /// inferred from the length of `Tamb`, and the function panics if the
+    /// length matches neither. When `nodes` is one, the two layouts coincide.
+    pub fn next_ambient(&mut self, P: &[f64], Tamb: &[f64], Q: &mut [f64]) {
+        let System { units, nodes, spots, .. } = *self.system;
+        let steps = P.len() / units;
+        debug_assert_eq!(P.len(), units * steps);
+        assert!(Tamb.len() == steps || Tamb.len() == nodes * steps,
+                "the number of ambient temperatures ({}) should be {} or {} for {} time steps",
+                Tamb.len(), steps, nodes * steps, steps);
+        let count = if Tamb.len() == steps { 1 } else { nodes };
+        debug_assert_eq!(Q.len(), spots * steps);
+        for i in 0..steps {
+            self.set_ambience(&Tamb[(i * count)..((i + 1) * count)]);
+            self.next(&P[(i * units)..((i + 1) * units)], &mut Q[(i * spots)..((i + 1) * spots)]);
+        }
+    }
+
+    /// Compute the thermal impedance.
+    ///
+    /// For each time point, the `spots × units` matrix whose `(k, j)`th
+    /// element is the rise of the temperature of spot `k` at the time point
+    /// after one Watt starts being dissipated by processing element `j` is
+    /// appended to `Z` in the column-major order. The state of the simulator
+    /// is not used. An error is returned if `Solver::Sparse` fails to reach
+    /// its tolerance.
+    pub fn impedance(&self, times: &[f64], Z: &mut [f64]) -> Result<()> {
+        let System { units, nodes, spots, .. } = *self.system;
+        debug_assert_eq!(Z.len(), spots * units * times.len());
+        let (zero, mut P) = (vec![0.0; nodes], vec![0.0; units]);
+        for (i, &time) in times.iter().enumerate() {
+            debug_assert!(time >= 0.0);
+            for j in 0..units {
+                let mut S = vec![0.0; nodes];
+                P[j] = 1.0;
+                self.system.propagate(time, &zero, &P, &mut S)?;
+                P[j] = 0.0;
+                let k = (i * units + j) * spots;
+                self.system.output(&S, &mut Z[k..(k + spots)]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the Foster network of the thermal impedance between a
+    /// processing element and a spot.
+    ///
+    /// The network is obtained from the eigendecomposition of the system and
+    /// has one stage per mode. Modes with the same time constant are merged,
+    /// and modes that do not couple the processing element with the spot are
+    /// omitted. The stages are sorted by their time constants.
+    pub fn foster(&self, unit: usize, spot: usize) -> Result<Foster> {
+        let System { units, nodes, spots, ref C, .. } = *self.system;
+        if unit >= units || spot >= spots {
+            raise!(InvalidInput, format!("the processing element {} or the spot {} is out of \
+                                          bounds", unit, spot));
+        }
+        let Dense { ref G, ref L, ref U, .. } = *self.system.decomposition()?;
+        let mut row = vec![0.0; nodes];
+        for (k, l, &value) in C.iter() {
+            if k == spot {
+                row[l] = value;
+            }
+        }
+        let mut terms = (0..nodes).map(|i| {
+            let column = &U.values[(i * nodes)..((i + 1) * nodes)];
+            let coupling = column.iter().zip(&row).fold(0.0, |sum, (&u, &c)| sum + u * c);
+            (-1.0 / L[i], -coupling * G[(i, unit)] / L[i])
+        }).collect::<Vec<_>>();
+        terms.sort_by(|one, other| one.0.partial_cmp(&other.0).unwrap());
+        let total = terms.iter().fold(0.0, |sum, &(_, resistance)| sum + resistance.abs());
+        let mut merged: Vec<(f64, f64)> = vec![];
+        for (time_constant, resistance) in terms {
+            if let Some(last) = merged.last_mut() {
+                if time_constant - last.0 <= 1e-10 * time_constant {
+                    last.1 += resistance;
+                    continue;
+                }
+            }
+            merged.push((time_constant, resistance));
+        }
+        let stages = merged.into_iter().filter(|&(_, resistance)| {
+            resistance.abs() > 1e-12 * total
+        }).map(|(time_constant, resistance)| {
+            Stage { resistance: resistance, capacitance: time_constant / resistance }
+        }).collect();
+        Ok(Foster { stages: stages })
+    }
+
+    /// Compute the transfer function.
+    ///
+    /// For each frequency in Hertz, the `spots × units` matrix
+    /// `H(jω) = C (jωI - A)^(-1) B`, where `ω` is the corresponding angular
+    /// frequency, is appended to `H` in the column-major order. The `(k, j)`th
+    /// element is the complex amplitude of the temperature of spot `k` in the
+    /// periodic steady state when processing element `j` dissipates a
+    /// sinusoidal power with the amplitude of one Watt.
+    pub fn transfer(&self, frequencies: &[f64], H: &mut [c64]) -> Result<()> {
+        let System { units, nodes, spots, ref C, .. } = *self.system;
+        debug_assert_eq!(H.len(), spots * units * frequencies.len());
+        let Dense { ref G, ref L, ref U, .. } = *self.system.decomposition()?;
+        let mut CU = vec![0.0; spots * nodes];
+        C.multiply_into(&U.values, &mut CU);
+        for (f, &frequency) in frequencies.iter().enumerate() {
+            debug_assert!(frequency >= 0.0);
+            let H = &mut H[(f * spots * units)..((f + 1) * spots * units)];
+            for value in H.iter_mut() {
+                *value = c64::new(0.0, 0.0);
+            }
+            for i in 0..nodes {
+                let factor = c64::new(1.0, 0.0) / c64::new(-L[i], 2.0 * PI * frequency);
+                for j in 0..units {
+                    let factor = factor * G[(i, j)];
+                    for k in 0..spots {
+                        H[j * spots + k] += factor * CU[i * spots + k];
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the Bode magnitude and phase of the transfer function.
+    ///
+    /// The magnitude is given in decibels relative to one Kelvin per Watt, and
+    /// the phase is given in degrees. The layout of `magnitude` and `phase` is
+    /// the same as the one of `H` in `transfer`.
+    pub fn bode(&self, frequencies: &[f64], magnitude: &mut [f64], phase: &mut [f64])
+                -> Result<()> {
+        debug_assert_eq!(magnitude.len(), phase.len());
+        let mut H = vec![c64::new(0.0, 0.0); magnitude.len()];
+        self.transfer(frequencies, &mut H)?;
+        for (i, value) in H.into_iter().enumerate() {
+            magnitude[i] = 20.0 * value.norm().log10();
+            phase[i] = value.arg().to_degrees();
+        }
+        Ok(())
+    }
+
+    /// Compute the temperature in the steady state.
+    ///
+    /// The power dissipation is assumed to be constant over time. Several
+    /// power vectors can be given at once, in which case the temperature is
+    /// computed for each of them independently. With `Solver::Sparse`, an
+    /// error is returned if the iterative solution fails to converge.
+    pub fn steady(&self, P: &[f64], Q: &mut [f64]) -> Result<()> {
+        let System { units, nodes, spots, ref C, ref model, .. } = *self.system;
+        let count = P.len() / units;
+        debug_assert_eq!(P.len(), units * count);
+        debug_assert_eq!(Q.len(), spots * count);
+        fill(Q, &self.offset);
+        match *model {
+            Model::Dense(ref dense, _) | Model::Modal(ref dense, _) |
+            Model::Reduced(ref dense, _) => dense.H.multiply_into(P, Q),
+            Model::Sparse(ref sparse) => for i in 0..count {
+                let mut S = vec![0.0; nodes];
+                sparse.solve(&P[(i * units)..((i + 1) * units)], &mut S)?;
+                C.multiply_into(&S, &mut Q[(i * spots)..((i + 1) * spots)]);
+            },
+        }
+        Ok(())
+    }
+
+    /// Return the temperature of the thermal nodes.
+    pub fn temperature(&self) -> Vec<f64> {
+        let System { nodes, ref D, .. } = *self.system;
+        let ambience = &self.ambience;
+        self.system.lift(self.state.current(nodes)).iter().enumerate().map(|(i, &value)| {
+            D[i] * value + ambience[i]
+        }).collect()
+    }
+
+    /// Set the temperature of the thermal nodes.
+    pub fn set_temperature(&mut self, T: &[f64]) {
+        let System { nodes, ref D, .. } = *self.system;
+        debug_assert_eq!(T.len(), nodes);
+        let ambience = &self.ambience;
+        let S = (0..nodes).map(|i| (T[i] - ambience[i]) / D[i]).collect::<Vec<_>>();
+        self.state.current_mut(nodes).copy_from_slice(&self.system.project(S));
+    }
+
+    /// Return the ambient temperature of the thermal nodes.
+    #[inline]
+    pub fn ambience(&self) -> &[f64] {
+        &self.ambience
+    }
+
+    /// Set the ambient temperature.
+    ///
+    /// `Tamb` contains either a single value, in which case the ambience is
+    /// uniform, or `nodes` values, in which case the ambience in the
+    /// configuration is set to their average. The temperature of the thermal
+    /// nodes is preserved.
+    pub fn set_ambience(&mut self, Tamb: &[f64]) {
+        let System { nodes, spots, ref D, ref Mq, .. } = *self.system;
+        let mut delta = vec![0.0; nodes];
+        if Tamb.len() == 1 {
+            for i in 0..nodes {
+                delta[i] = (self.ambience[i] - Tamb[0]) / D[i];
+                self.ambience[i] = Tamb[0];
+            }
+            self.offset = vec![Tamb[0]; spots];
+            self.config.ambience = Tamb[0];
+        } else {
+            debug_assert_eq!(Tamb.len(), nodes);
+            for i in 0..nodes {
+                delta[i] = (self.ambience[i] - Tamb[i]) / D[i];
+                self.ambience[i] = Tamb[i];
+            }
+            self.offset = vec![0.0; spots];
+            Mq.multiply_into(Tamb, &mut self.offset);
+            self.config.ambience = Tamb.iter().sum::<f64>() / nodes as f64;
+        }
+        let current = self.state.current_mut(nodes);
+        for (value, delta) in current.iter_mut().zip(self.system.project(delta)) {
+            *value += delta;
+        }
+    }
+
+    /// Set the temperature of the thermal nodes to the steady state
+    /// corresponding to a power vector.
+    ///
+    /// With `Solver::Sparse`, an error is returned if the iterative solution
+    /// fails to converge, in which case the temperature is reset to the
+    /// ambience.
+    pub fn settle(&mut self, P: &[f64]) -> Result<()> {
+        let System { units, nodes, ref model, .. } = *self.system;
+        debug_assert_eq!(P.len(), units);
+        let current = self.state.current_mut(nodes);
+        for value in current.iter_mut() {
+            *value = 0.0;
+        }
+        match *model {
+            Model::Dense(ref dense, _) | Model::Reduced(ref dense, _) => {
+                dense.Z.multiply_into(P, current)
+            },
+            Model::Modal(ref dense, _) => {
+                let T = dense.G.multiply(P);
+                for i in 0..nodes {
+                    current[i] = -T[i] / dense.L[i];
+                }
+            },
+            Model::Sparse(ref sparse) => sparse.solve(P, current)?,
+        }
+        Ok(())
+    }
+
+    /// Reset the temperature of the thermal nodes to the ambience and the
+    /// step counter to zero.
+    pub fn reset(&mut self) {
+        let System { nodes, .. } = *self.system;
+        for value in self.state.current_mut(nodes).iter_mut() {
+            *value = 0.0;
+        }
+        self.steps = 0;
+    }
+
+    /// Take a snapshot of the state.
+    pub fn snapshot(&self) -> Snapshot {
+        let System { nodes, .. } = *self.system;
+        Snapshot {
+            average: self.config.ambience,
+            ambience: self.ambience.clone(),
+            offset: self.offset.clone(),
+            state: self.state.current(nodes).to_vec(),
+            steps: self.steps,
+        }
+    }
+
+    /// Restore the state from a snapshot.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let System { nodes, .. } = *self.system;
+        debug_assert_eq!(snapshot.state.len(), nodes);
+        self.config.ambience = snapshot.average;
+        self.ambience.copy_from_slice(&snapshot.ambience);
+        self.offset.copy_from_slice(&snapshot.offset);
+        self.state.current_mut(nodes).copy_from_slice(&snapshot.state);
+        self.steps = snapshot.steps;
+    }
+
+    /// Create a simulator that shares the precomputed matrices with the
+    /// current one and starts from a snapshot.
+    pub fn fork(&self, snapshot: &Snapshot) -> Simulator {
+        let System { nodes, .. } = *self.system;
+        debug_assert_eq!(snapshot.state.len(), nodes);
+        Simulator {
+            config: Config { ambience: snapshot.average, ..self.config },
+            system: self.system.clone(),
+            propagator: self.propagator.clone(),
+            propagators: self.propagators.clone(),
+            factors: self.factors.clone(),
+            ambience: snapshot.ambience.clone(),
+            offset: snapshot.offset.clone(),
+            state: State::from(snapshot),
+            steps: snapshot.steps,
+        }
+    }
+
+    /// Change the time step.
+    ///
+    /// The eigendecomposition of the system is reused, and the matrices
+    /// corresponding to each time step are cached so that switching between
+    /// several time steps is cheap. With `Solver::Modal`, `Solver::Reduced`,
+    /// and `Solver::Sparse`, only a diagonal scaling is recomputed.
+    pub fn set_time_step(&mut self, time_step: f64) {
+        debug_assert!(time_step > 0.0);
+        self.propagator = self.propagator(time_step);
+        self.factors = Arc::new(Factors::new(self.system.eigenvalues(), time_step));
+        self.config.time_step = time_step;
+    }
+
+    /// Return the number of time steps performed so far.
+    #[inline]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Return the system.
+    #[inline]
+    pub fn system(&self) -> &Arc<System> {
+        &self.system
+    }
+
+    /// Return the configuration.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn step(&mut self, P: &[f64], Q: &mut [f64]) -> Result<()> {
+        let System { units, nodes, spots, .. } = *self.system;
+        let S = &mut self.state;
+        let steps = P.len() / units;
+        debug_assert_eq!(P.len(), units * steps);
+        debug_assert_eq!(Q.len(), spots * steps);
+        S.next(nodes, steps);
+        match (&self.system.model, &self.propagator) {
+            (&Model::Modal(ref dense, ref modal), _) => modal.next(dense, &self.factors, P, S),
+            (&Model::Reduced(_, ref reduced), _) => reduced.next(&self.factors, P, S),
+            (_, &Some(ref propagator)) => {
+                let Propagator { ref E, ref F, .. } = **propagator;
+                F.multiply_into(P, &mut S[nodes..]);
+                for i in 0..steps {
+                    let (from, into) = S[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
+                    E.multiply_into(from, into);
+                }
+            },
+            _ => for i in 0..steps {
+                let (from, into) = S[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
+                let P = &P[(i * units)..((i + 1) * units)];
+                if let Err(error) = self.system.advance(&self.factors, from, P, into) {
+                    S.revert(nodes);
+                    return Err(error);
+                }
+            },
+        }
+        fill(Q, &self.offset);
+        self.system.output(&S[nodes..], Q);
+        self.steps += steps;
+        Ok(())
+    }
+
+    fn observe(&self, Q: &mut [f64]) {
+        let System { nodes, .. } = *self.system;
+        fill(Q, &self.offset);
+        self.system.output(self.state.current(nodes), Q);
+    }
+
+    fn propagator(&mut self, time_step: f64) -> Option<Arc<Propagator>> {
+        let (dense, propagator) = match self.system.model {
+            Model::Dense(ref dense, ref propagator) => (dense, propagator),
+            _ => return None,
+        };
+        if propagator.time_step == time_step {
+            return Some(propagator.clone());
+        }
+        Some(self.propagators.entry(time_step.to_bits()).or_insert_with(|| {
+            Arc::new(Propagator::new(&dense.U, &dense.L, &dense.G, time_step))
+        }).clone())
+    }
+}
+
+impl From<Arc<System>> for Simulator {
+    /// Create a simulator sharing the precomputed matrices of a system.
+    fn from(system: Arc<System>) -> Simulator {
+        Simulator {
+            config: system.config,
+            propagator: match system.model {
+                Model::Dense(_, ref propagator) => Some(propagator.clone()),
+                _ => None,
+            },
+            propagators: HashMap::new(),
+            factors: Arc::new(Factors::new(system.eigenvalues(), system.config.time_step)),
+            ambience: vec![system.config.ambience; system.nodes],
+            offset: vec![system.config.ambience; system.spots],
+            state: State::new(system.nodes),
+            steps: 0,
+            system: system,
+        }
+    }
+}
+
+impl System {
+    /// Create a system.
+    pub fn new(circuit: Circuit, config: Config) -> Result<System> {
+        circuit.check(match config.solver {
+            Solver::Sparse { .. } => false,
+            _ => true,
+        })?;
+        let checksum = circuit.checksum();
+        let Circuit {
+            capacitance, conductance, distribution, aggregation, units: unit_names,
+            spots: spot_names,
+        } = circuit;
         let ((nodes, units), spots) = (distribution.dimensions(), aggregation.rows());
-        debug_assert_eq!(aggregation.columns(), nodes);
         let mut D = capacitance;
         for value in D.iter_mut() {
             *value = (1.0 / *value).sqrt();
@@ -44,59 +686,259 @@ impl Simulator {
         for (i, j, value) in A.iter_mut() {
             *value *= -D[i] * D[j];
         }
+        let C = aggregation.multiply(&D);
+        let model = match config.solver {
+            Solver::Dense => {
+                let dense = Dense::new(A, &D, &distribution, &C)?;
+                let propagator = Propagator::new(&dense.U, &dense.L, &dense.G, config.time_step);
+                Model::Dense(dense, Arc::new(propagator))
+            },
+            Solver::Modal => {
+                let dense = Dense::new(A, &D, &distribution, &C)?;
+                let modal = Modal::new(&dense, &C);
+                Model::Modal(dense, modal)
+            },
+            Solver::Reduced(reduction) => {
+                let dense = Dense::new(A, &D, &distribution, &C)?;
+                let reduced = Reduced::new(&dense, &C, reduction)?;
+                Model::Reduced(dense, reduced)
+            },
+            Solver::Sparse { tolerance } => {
+                if tolerance.is_nan() || tolerance <= 0.0 || tolerance >= 1.0 {
+                    raise!(InvalidInput, format!("the tolerance should be in (0, 1) but is {}",
+                                                 tolerance));
+                }
+                let mut B = distribution;
+                for (i, _, value) in B.iter_mut() {
+                    *value *= D[i];
+                }
+                Model::Sparse(Sparse { A: A, B: B, tolerance: tolerance })
+            },
+        };
+        Ok(System {
+            config: config,
+            units: units, nodes: nodes, spots: spots,
+            C: C, D: D, Mq: aggregation,
+            unit_names: unit_names,
+            spot_names: spot_names,
+            checksum: checksum,
+            model: model,
+        })
+    }
+
+    /// Return the configuration.
+    #[inline]
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Return the checksum of the circuit that the system was created for.
+    ///
+    /// The value is the one of `Circuit::checksum`.
+    #[inline]
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+
+    /// Return the number of modes used for advancing the state.
+    ///
+    /// The number is smaller than the number of thermal nodes only when the
+    /// order of the system is reduced.
+    pub fn order(&self) -> usize {
+        match self.model {
+            Model::Reduced(_, ref reduced) => reduced.L.len(),
+            _ => self.nodes,
+        }
+    }
+
+    /// Return the bound on the error introduced by the order reduction.
+    ///
+    /// When the simulation starts from a steady state, the absolute error of
+    /// the temperature of interest computed by `next` does not exceed the
+    /// bound multiplied by the maximal absolute power of the processing
+    /// elements. The bound is given in Kelvin per Watt. It is zero when the
+    /// order of the system is not reduced.
+    pub fn bound(&self) -> f64 {
+        match self.model {
+            Model::Reduced(_, ref reduced) => reduced.bound,
+            _ => 0.0,
+        }
+    }
+
+    /// Return the names of the processing elements, which are empty if
+    /// omitted in the circuit.
+    #[inline]
+    pub fn units(&self) -> &[String] {
+        &self.unit_names
+    }
+
+    /// Return the names of the spots, which are empty if omitted in the
+    /// circuit.
+    #[inline]
+    pub fn spots(&self) -> &[String] {
+        &self.spot_names
+    }
+
+    /// Return the index of a processing element given its name.
+    pub fn unit(&self, name: &str) -> Option<usize> {
+        self.unit_names.iter().position(|unit| unit == name)
+    }
+
+    /// Return the index of a spot given its name.
+    pub fn spot(&self, name: &str) -> Option<usize> {
+        self.spot_names.iter().position(|spot| spot == name)
+    }
+
+    /// Compute the temperature of interest, excluding the ambience, and add the
+    /// result to `Q`.
+    fn output(&self, S: &[f64], Q: &mut [f64]) {
+        match self.model {
+            Model::Modal(_, ref modal) => modal.CU.multiply_into(S, Q),
+            _ => self.C.multiply_into(S, Q),
+        }
+    }
+
+    /// Convert a state into the node space.
+    fn lift(&self, S: &[f64]) -> Vec<f64> {
+        match self.model {
+            Model::Modal(ref dense, _) => dense.U.multiply(S).values,
+            _ => S.to_vec(),
+        }
+    }
+
+    /// Convert a state from the node space.
+    fn project(&self, S: Vec<f64>) -> Vec<f64> {
+        let System { nodes, ref model, .. } = *self;
+        match *model {
+            Model::Modal(ref dense, _) => (0..nodes).map(|i| {
+                let column = &dense.U.values[(i * nodes)..((i + 1) * nodes)];
+                column.iter().zip(&S).fold(0.0, |sum, (&u, &s)| sum + u * s)
+            }).collect(),
+            _ => S,
+        }
+    }
+
+    fn decomposition(&self) -> Result<&Dense> {
+        match self.model {
+            Model::Dense(ref dense, _) | Model::Modal(ref dense, _) |
+            Model::Reduced(ref dense, _) => Ok(dense),
+            Model::Sparse(_) => raise!(Unsupported, "the sparse solver does not provide the \
+                                                     eigendecomposition"),
+        }
+    }
+
+    /// Return the eigenvalues used for advancing the state, which are absent
+    /// with `Solver::Sparse`.
+    fn eigenvalues(&self) -> &[f64] {
+        match self.model {
+            Model::Dense(ref dense, _) | Model::Modal(ref dense, _) => &dense.L,
+            Model::Reduced(_, ref reduced) => &reduced.L,
+            Model::Sparse(_) => &[],
+        }
+    }
+
+    /// Advance the state `S` by `time_step` seconds under the power `P` and
+    /// add the result to `into`.
+    fn propagate(&self, time_step: f64, S: &[f64], P: &[f64], into: &mut [f64])
+                 -> Result<()> {
+        self.advance(&Factors::new(self.eigenvalues(), time_step), S, P, into)
+    }
+
+    /// Advance the state `S` by the time step of `factors` under the power `P`
+    /// and add the result to `into`.
+    fn advance(&self, factors: &Factors, S: &[f64], P: &[f64], into: &mut [f64])
+               -> Result<()> {
+        let System { nodes, ref model, .. } = *self;
+        let Dense { ref G, ref U, .. } = match *model {
+            Model::Dense(ref dense, _) => dense,
+            Model::Modal(ref dense, ref modal) => {
+                modal.propagate(dense, factors, S, P, into);
+                return Ok(());
+            },
+            Model::Reduced(_, ref reduced) => {
+                reduced.propagate(factors, S, P, into);
+                return Ok(());
+            },
+            Model::Sparse(ref sparse) => {
+                return sparse.propagate(factors.time_step, S, P, into)
+            },
+        };
+        let mut T = G.multiply(P);
+        for i in 0..nodes {
+            let column = &U.values[(i * nodes)..((i + 1) * nodes)];
+            let projection = column.iter().zip(S).fold(0.0, |sum, (&u, &s)| sum + u * s);
+            T[i] = factors.exponent[i] * projection + factors.scale[i] * T[i];
+        }
+        U.multiply_into(&T, into);
+        Ok(())
+    }
+}
+
+impl Dense {
+    fn new(A: Compressed<f64>, D: &Diagonal<f64>, distribution: &Compressed<f64>,
+           C: &Compressed<f64>) -> Result<Dense> {
+        let ((nodes, units), spots) = (distribution.dimensions(), C.rows());
         let A = Conventional::from(A);
-        let (U, L) = ok!(SymmetricEigen::decompose(&A));
-        let mut T1 = A;
-        let mut T2 = Conventional::zero(nodes);
+        let (U, L) = ok!(SymmetricEigen::decompose(&A), Numerical);
+        let mut T = A;
         for i in 0..nodes {
-            let factor = ((config.time_step * L[i]).exp() - 1.0) / L[i];
             for j in 0..nodes {
-                T1[(i, j)] = factor * U[(j, i)] * D[j];
+                T[(i, j)] = U[(j, i)] * D[j];
             }
         }
-        T1.multiply_into(&distribution, &mut T2.values[..(nodes * units)]);
-        let F = U.multiply(&T2.values[..(nodes * units)]);
+        let mut G = Conventional::zero((nodes, units));
+        T.multiply_into(distribution, &mut G);
+        let mut T = G.clone();
         for i in 0..nodes {
-            let factor = (config.time_step * L[i]).exp();
-            for j in 0..nodes {
-                T1[(i, j)] = factor * U[(j, i)];
+            let factor = -1.0 / L[i];
+            for j in 0..units {
+                T[(i, j)] = factor * G[(i, j)];
             }
         }
-        let mut E = T2;
-        unsafe { E.erase() };
-        U.multiply_into(&T1, &mut E);
-        let C = aggregation.multiply(&D);
-        Ok(Simulator {
-            config: config,
-            system: System {
-                units: units, nodes: nodes, spots: spots,
-                C: C, E: E, F: F, S: State::new(nodes),
-            },
-        })
+        let Z = U.multiply(&T);
+        let mut H = Conventional::zero((spots, units));
+        C.multiply_into(&Z, &mut H);
+        Ok(Dense { G: G, H: H, L: L, U: U, Z: Z })
     }
+}
 
-    /// Perform the simulation.
-    pub fn next(&mut self, P: &[f64], Q: &mut [f64]) {
-        let Config { ambience, .. } = self.config;
-        let System { units, nodes, spots, ref C, ref E, ref F, ref mut S } = self.system;
-        let steps = P.len() / units;
-        debug_assert_eq!(P.len(), units * steps);
-        debug_assert_eq!(Q.len(), spots * steps);
-        S.next(nodes, steps);
-        F.multiply_into(P, &mut S[nodes..]);
-        for i in 0..steps {
-            let (from, into) = S[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
-            E.multiply_into(from, into);
+impl Propagator {
+    fn new(U: &Conventional<f64>, L: &Diagonal<f64>, G: &Conventional<f64>, time_step: f64)
+           -> Propagator {
+        let (nodes, units) = G.dimensions();
+        let mut T = G.clone();
+        for i in 0..nodes {
+            let factor = ((time_step * L[i]).exp() - 1.0) / L[i];
+            for j in 0..units {
+                T[(i, j)] *= factor;
+            }
         }
-        for value in Q.iter_mut() {
-            *value = ambience;
+        let F = U.multiply(&T);
+        let mut T = Conventional::zero(nodes);
+        for i in 0..nodes {
+            let factor = (time_step * L[i]).exp();
+            for j in 0..nodes {
+                T[(i, j)] = factor * U[(j, i)];
+            }
         }
-        C.multiply_into(&S[nodes..], Q);
+        let E = U.multiply(&T);
+        Propagator { time_step: time_step, E: E, F: F }
     }
+}
 
-    /// Return the configuration.
-    pub fn config(&self) -> &Config {
-        &self.config
+impl Factors {
+    fn new(L: &[f64], time_step: f64) -> Factors {
+        let exponent = L.iter().map(|&l| (time_step * l).exp()).collect::<Vec<_>>();
+        let scale = exponent.iter().zip(L).map(|(&e, &l)| (e - 1.0) / l).collect();
+        Factors { time_step: time_step, exponent: exponent, scale: scale }
+    }
+}
+
+impl Snapshot {
+    /// Return the number of time steps performed before the snapshot.
+    #[inline]
+    pub fn steps(&self) -> usize {
+        self.steps
     }
 }
 
@@ -105,6 +947,29 @@ impl State {
         State(vec![0.0; 2 * nodes])
     }
 
+    fn from(snapshot: &Snapshot) -> State {
+        let mut buffer = snapshot.state.clone();
+        buffer.extend_from_slice(&snapshot.state);
+        State(buffer)
+    }
+
+    #[inline]
+    fn current(&self, nodes: usize) -> &[f64] {
+        &self.0[(self.0.len() - nodes)..]
+    }
+
+    #[inline]
+    fn current_mut(&mut self, nodes: usize) -> &mut [f64] {
+        let length = self.0.len();
+        &mut self.0[(length - nodes)..]
+    }
+
+    // Restore the current state to the one preceding the last call to `next`.
+    fn revert(&mut self, nodes: usize) {
+        let length = self.0.len();
+        self.0.copy_within(0..nodes, length - nodes);
+    }
+
     fn next(&mut self, nodes: usize, steps: usize) {
         let buffer = &mut self.0;
         let current = buffer.len();
@@ -124,6 +989,13 @@ impl State {
     }
 }
 
+fn fill(Q: &mut [f64], offset: &[f64]) {
+    let spots = offset.len();
+    for (i, value) in Q.iter_mut().enumerate() {
+        *value = offset[i % spots];
+    }
+}
+
 impl Deref for State {
     type Target = [f64];
 
@@ -0,0 +1,50 @@
+use matrix::format::{Compressed, Conventional};
+use matrix::operation::{Multiply, MultiplyInto};
+use matrix::{Matrix, Size};
+
+use super::{Dense, Factors};
+
+/// A model that advances the state in the eigenbasis.
+///
+/// The state is given by `U^T S` instead of `S`.
+pub struct Modal {
+    /// The matrix `C U`.
+    pub CU: Conventional<f64>,
+}
+
+impl Modal {
+    /// Create a model.
+    pub fn new(dense: &Dense, C: &Compressed<f64>) -> Modal {
+        let mut CU = Conventional::zero((C.rows(), dense.U.columns()));
+        C.multiply_into(&dense.U.values, &mut CU.values);
+        Modal { CU: CU }
+    }
+
+    /// Perform the simulation.
+    ///
+    /// `S` should contain `steps + 1` states, the first of which is the
+    /// current one and the rest of which are zero.
+    pub fn next(&self, dense: &Dense, factors: &Factors, P: &[f64], S: &mut [f64]) {
+        let Factors { ref exponent, ref scale, .. } = *factors;
+        let (nodes, units) = dense.G.dimensions();
+        let steps = P.len() / units;
+        dense.G.multiply_into(P, &mut S[nodes..]);
+        for i in 0..steps {
+            let (from, into) = S[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
+            for j in 0..nodes {
+                into[j] = exponent[j] * from[j] + scale[j] * into[j];
+            }
+        }
+    }
+
+    /// Advance the state `S` by the time step of `factors` under the power `P`
+    /// and add the result to `into`.
+    pub fn propagate(&self, dense: &Dense, factors: &Factors, S: &[f64], P: &[f64],
+                     into: &mut [f64]) {
+        let Factors { ref exponent, ref scale, .. } = *factors;
+        let T = dense.G.multiply(P);
+        for i in 0..S.len() {
+            into[i] += exponent[i] * S[i] + scale[i] * T[i];
+        }
+    }
+}
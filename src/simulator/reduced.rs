@@ -0,0 +1,146 @@
+use matrix::format::{Compressed, Conventional};
+use matrix::operation::MultiplyInto;
+use matrix::{Matrix, Size};
+
+use super::{Dense, Factors};
+use circuit;
+use {Reduction, Result};
+
+/// A model that retains only the dominant modes.
+pub struct Reduced {
+    /// The eigenvalues of the retained modes.
+    pub L: Vec<f64>,
+    /// The rows of `G` corresponding to the retained modes.
+    pub G: Conventional<f64>,
+    /// The eigenvectors of the retained modes.
+    pub V: Conventional<f64>,
+    /// The transpose of `V`.
+    pub W: Conventional<f64>,
+    /// The matrix mapping the power onto the residualized part of the state.
+    pub R: Conventional<f64>,
+    /// The error bound in Kelvin per Watt.
+    pub bound: f64,
+}
+
+impl Reduced {
+    /// Reduce a dense model.
+    pub fn new(dense: &Dense, C: &Compressed<f64>, reduction: Reduction) -> Result<Reduced> {
+        let Dense { ref G, ref L, ref U, .. } = *dense;
+        let ((nodes, units), spots) = (G.dimensions(), C.rows());
+        let mut CU = vec![0.0; spots * nodes];
+        C.multiply_into(&U.values, &mut CU);
+        let contributions = (0..nodes).map(|i| {
+            let coupling = (0..units).map(|j| G[(i, j)]).collect::<Vec<_>>();
+            circuit::contributions(&CU[(i * spots)..((i + 1) * spots)], &coupling, L[i])
+        }).collect::<Vec<_>>();
+        let mut order = (0..nodes).collect::<Vec<_>>();
+        order.sort_by(|&i, &j| {
+            let (i, j) = (maximum(&contributions[i]), maximum(&contributions[j]));
+            j.partial_cmp(&i).unwrap()
+        });
+        let mut errors = vec![0.0; spots];
+        let count = match reduction {
+            Reduction::Modes(count) => {
+                if count == 0 || count > nodes {
+                    raise!(InvalidInput, format!("the number of modes should be between 1 and {} \
+                                                  but is {}", nodes, count));
+                }
+                for &i in &order[count..] {
+                    accumulate(&mut errors, &contributions[i]);
+                }
+                count
+            },
+            Reduction::Tolerance(tolerance) => {
+                if tolerance.is_nan() || tolerance < 0.0 {
+                    raise!(InvalidInput, format!("the tolerance should be nonnegative but is {}",
+                                                 tolerance));
+                }
+                let mut count = nodes;
+                while count > 1 {
+                    let mut candidate = errors.clone();
+                    accumulate(&mut candidate, &contributions[order[count - 1]]);
+                    if 2.0 * maximum(&candidate) > tolerance {
+                        break;
+                    }
+                    errors = candidate;
+                    count -= 1;
+                }
+                count
+            },
+        };
+        let (retained, discarded) = order.split_at(count);
+        let mut V = Conventional::zero((nodes, count));
+        let mut W = Conventional::zero((count, nodes));
+        let mut reduced = Conventional::zero((count, units));
+        for (k, &i) in retained.iter().enumerate() {
+            for l in 0..nodes {
+                V[(l, k)] = U[(l, i)];
+                W[(k, l)] = U[(l, i)];
+            }
+            for j in 0..units {
+                reduced[(k, j)] = G[(i, j)];
+            }
+        }
+        let mut R = Conventional::zero((nodes, units));
+        for &i in discarded {
+            for j in 0..units {
+                let factor = -G[(i, j)] / L[i];
+                for l in 0..nodes {
+                    R[(l, j)] += U[(l, i)] * factor;
+                }
+            }
+        }
+        Ok(Reduced {
+            L: retained.iter().map(|&i| L[i]).collect(),
+            G: reduced, V: V, W: W, R: R,
+            bound: 2.0 * maximum(&errors),
+        })
+    }
+
+    /// Perform the simulation.
+    ///
+    /// `S` should contain `steps + 1` states, the first of which is the
+    /// current one and the rest of which are zero.
+    pub fn next(&self, factors: &Factors, P: &[f64], S: &mut [f64]) {
+        let ((nodes, units), count) = (self.R.dimensions(), self.L.len());
+        let steps = P.len() / units;
+        self.R.multiply_into(P, &mut S[nodes..]);
+        let mut buffer = vec![0.0; 2 * count];
+        for i in 0..steps {
+            let (from, into) = S[(i * nodes)..((i + 2) * nodes)].split_at_mut(nodes);
+            self.advance(factors, from, &P[(i * units)..((i + 1) * units)], into, &mut buffer);
+        }
+    }
+
+    /// Advance the state `S` by the time step of `factors` under the power `P`
+    /// and add the result to `into`.
+    pub fn propagate(&self, factors: &Factors, S: &[f64], P: &[f64], into: &mut [f64]) {
+        self.R.multiply_into(P, into);
+        self.advance(factors, S, P, into, &mut vec![0.0; 2 * self.L.len()]);
+    }
+
+    fn advance(&self, factors: &Factors, S: &[f64], P: &[f64], into: &mut [f64],
+               buffer: &mut [f64]) {
+        let Factors { ref exponent, ref scale, .. } = *factors;
+        for value in buffer.iter_mut() {
+            *value = 0.0;
+        }
+        let (X, Y) = buffer.split_at_mut(self.L.len());
+        self.W.multiply_into(S, X);
+        self.G.multiply_into(P, Y);
+        for k in 0..X.len() {
+            X[k] = exponent[k] * X[k] + scale[k] * Y[k];
+        }
+        self.V.multiply_into(X, into);
+    }
+}
+
+fn accumulate(errors: &mut [f64], contributions: &[f64]) {
+    for (error, &contribution) in errors.iter_mut().zip(contributions) {
+        *error += contribution;
+    }
+}
+
+fn maximum(values: &[f64]) -> f64 {
+    values.iter().fold(0.0, |maximum: f64, &value| maximum.max(value))
+}
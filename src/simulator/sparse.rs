@@ -0,0 +1,197 @@
+use matrix::decomposition::SymmetricEigen;
+use matrix::format::{Compressed, Conventional};
+use matrix::operation::MultiplyInto;
+use matrix::Matrix;
+
+use Result;
+
+const DIMENSION: usize = 64;
+const DEPTH: usize = 20;
+
+/// A model that keeps the system sparse.
+pub struct Sparse {
+    /// The matrix `A = -D Gth D`.
+    pub A: Compressed<f64>,
+    /// The matrix `B = D Mp`.
+    pub B: Compressed<f64>,
+    /// The relative tolerance.
+    pub tolerance: f64,
+}
+
+impl Sparse {
+    /// Advance the state `S` by `time_step` seconds under the power `P` and
+    /// add the result to `into`.
+    pub fn propagate(&self, time_step: f64, S: &[f64], P: &[f64], into: &mut [f64])
+                     -> Result<()> {
+        let result = self.advance(time_step, S, P, 0)?;
+        for (into, value) in into.iter_mut().zip(result) {
+            *into += value;
+        }
+        Ok(())
+    }
+
+    /// Compute the state corresponding to the steady state under the power
+    /// `P` and add the result to `into`.
+    pub fn solve(&self, P: &[f64], into: &mut [f64]) -> Result<()> {
+        let nodes = self.A.rows;
+        let mut b = vec![0.0; nodes];
+        self.B.multiply_into(P, &mut b);
+        let mut diagonal = vec![0.0; nodes];
+        for (i, j, &value) in self.A.iter() {
+            if i == j {
+                diagonal[i] = -value;
+            }
+        }
+        let mut x = vec![0.0; nodes];
+        let mut r = b.clone();
+        let mut z = r.iter().zip(&diagonal).map(|(&r, &d)| r / d).collect::<Vec<_>>();
+        let mut p = z.clone();
+        let mut rz = dot(&r, &z);
+        let limit = self.tolerance * norm(&b);
+        for _ in 0..(10 * nodes) {
+            if norm(&r) <= limit {
+                for (into, value) in into.iter_mut().zip(x) {
+                    *into += value;
+                }
+                return Ok(());
+            }
+            let mut q = vec![0.0; nodes];
+            self.A.multiply_into(&p, &mut q);
+            for value in q.iter_mut() {
+                *value = -*value;
+            }
+            let alpha = rz / dot(&p, &q);
+            for i in 0..nodes {
+                x[i] += alpha * p[i];
+                r[i] -= alpha * q[i];
+                z[i] = r[i] / diagonal[i];
+            }
+            let previous = rz;
+            rz = dot(&r, &z);
+            for i in 0..nodes {
+                p[i] = z[i] + rz / previous * p[i];
+            }
+        }
+        raise!(Numerical, "the conjugate-gradient method failed to converge")
+    }
+
+    fn advance(&self, time_step: f64, S: &[f64], P: &[f64], depth: usize)
+               -> Result<Vec<f64>> {
+        let mut w = vec![0.0; S.len()];
+        self.A.multiply_into(S, &mut w);
+        self.B.multiply_into(P, &mut w);
+        if !norm(&w).is_finite() {
+            raise!(Numerical, "the state or the power is not finite");
+        }
+        match self.phi(time_step, &w) {
+            Some(mut result) => {
+                for (value, &s) in result.iter_mut().zip(S) {
+                    *value = s + time_step * *value;
+                }
+                Ok(result)
+            },
+            _ if depth == DEPTH => {
+                raise!(Numerical, "the Krylov approximation failed to reach the tolerance")
+            },
+            _ => {
+                let middle = self.advance(time_step / 2.0, S, P, depth + 1)?;
+                self.advance(time_step / 2.0, &middle, P, depth + 1)
+            },
+        }
+    }
+
+    /// Compute `φ1(A time_step) w` using the Lanczos process.
+    ///
+    /// `None` is returned if the tolerance is not reached within the maximal
+    /// dimension of the Krylov subspace.
+    fn phi(&self, time_step: f64, w: &[f64]) -> Option<Vec<f64>> {
+        let nodes = w.len();
+        let beta = norm(w);
+        if beta == 0.0 {
+            return Some(vec![0.0; nodes]);
+        }
+        let limit = DIMENSION.min(nodes);
+        let mut V = vec![w.iter().map(|&value| value / beta).collect::<Vec<_>>()];
+        let (mut alphas, mut betas) = (vec![], vec![]);
+        let mut previous: Vec<f64> = vec![];
+        let mut scale: f64 = 0.0;
+        loop {
+            let j = V.len() - 1;
+            let mut u = vec![0.0; nodes];
+            self.A.multiply_into(&V[j], &mut u);
+            let alpha = dot(&V[j], &u);
+            for i in 0..nodes {
+                u[i] -= alpha * V[j][i];
+                if j > 0 {
+                    u[i] -= betas[j - 1] * V[j - 1][i];
+                }
+            }
+            alphas.push(alpha);
+            let next = norm(&u);
+            scale = scale.max(alpha.abs()).max(next);
+            let y = match phi_tridiagonal(time_step, &alphas, &betas) {
+                Some(y) => y,
+                _ => return None,
+            };
+            let change = y.iter().enumerate().fold(0.0, |sum, (i, &value)| {
+                let delta = value - previous.get(i).cloned().unwrap_or(0.0);
+                sum + delta * delta
+            }).sqrt();
+            let exhausted = next <= 1e-12 * scale;
+            if exhausted || change <= self.tolerance * norm(&y) {
+                let mut result = vec![0.0; nodes];
+                for (v, &c) in V.iter().zip(&y) {
+                    for i in 0..nodes {
+                        result[i] += beta * c * v[i];
+                    }
+                }
+                return Some(result);
+            }
+            if alphas.len() == limit {
+                return None;
+            }
+            betas.push(next);
+            V.push(u.iter().map(|&value| value / next).collect());
+            previous = y;
+        }
+    }
+}
+
+/// Compute `φ1(T time_step) e1` for a symmetric tridiagonal matrix `T`.
+///
+/// `None` is returned if the decomposition fails or the result is not finite.
+fn phi_tridiagonal(time_step: f64, alphas: &[f64], betas: &[f64]) -> Option<Vec<f64>> {
+    let m = alphas.len();
+    let mut T = Conventional::zero(m);
+    for i in 0..m {
+        T[(i, i)] = alphas[i];
+        if i + 1 < m {
+            T[(i, i + 1)] = betas[i];
+            T[(i + 1, i)] = betas[i];
+        }
+    }
+    let (U, L) = match SymmetricEigen::decompose(&T) {
+        Ok(result) => result,
+        _ => return None,
+    };
+    let mut y = vec![0.0; m];
+    for k in 0..m {
+        let factor = phi(time_step * L[k]) * U[(0, k)];
+        for i in 0..m {
+            y[i] += factor * U[(i, k)];
+        }
+    }
+    if y.iter().all(|value| value.is_finite()) { Some(y) } else { None }
+}
+
+fn phi(z: f64) -> f64 {
+    if z.abs() < 1e-8 { 1.0 + z / 2.0 } else { z.exp_m1() / z }
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).fold(0.0, |sum, (&x, &y)| sum + x * y)
+}
+
+fn norm(x: &[f64]) -> f64 {
+    dot(x, x).sqrt()
+}
@@ -0,0 +1,333 @@
+use matrix::format::compressed::Variant;
+use matrix::format::{Compressed, Conventional, Diagonal};
+use matrix::Size;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind as IoErrorKind, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use super::{Dense, Model, Propagator, Simulator, System};
+use {Circuit, Config, Result, Solver};
+
+const MAGIC: &'static [u8; 8] = b"TEMPSYS\0";
+const VERSION: u32 = 2;
+
+impl System {
+    /// Save the system to a file.
+    ///
+    /// The file contains all the precomputed matrices along with a format
+    /// version and the checksum of the circuit that the system was created
+    /// for. The data are stored in the little-endian order. Only systems
+    /// created with `Solver::Dense` can be saved.
+    pub fn save<T: AsRef<Path>>(&self, path: T) -> Result<()> {
+        let mut writer = BufWriter::new(ok!(File::create(path), Io));
+        self.write_to(&mut writer)?;
+        ok!(writer.flush(), Io);
+        Ok(())
+    }
+
+    /// Load a system from a file.
+    ///
+    /// No decomposition is performed. Use `load_for` or `checksum` in order to
+    /// check that the system corresponds to a particular circuit.
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<System> {
+        System::read_from(&mut BufReader::new(ok!(File::open(path), Io)))
+    }
+
+    /// Load a system from a file and check that it corresponds to a circuit.
+    ///
+    /// An error of kind `InvalidInput` is returned if the checksum stored in
+    /// the file differs from the one of the circuit.
+    pub fn load_for<T: AsRef<Path>>(path: T, circuit: &Circuit) -> Result<System> {
+        let system = System::load(path)?;
+        let checksum = circuit.checksum();
+        if system.checksum != checksum {
+            raise!(InvalidInput, format!("the system was created for a different circuit \
+                                          (checksum {:016x} instead of {:016x})",
+                                         system.checksum, checksum));
+        }
+        Ok(system)
+    }
+
+    /// Write the system to a writer.
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let System {
+            config, units, nodes, spots, ref C, ref D, ref Mq, ref unit_names, ref spot_names,
+            checksum, ref model,
+        } = *self;
+        let (dense, propagator) = match *model {
+            Model::Dense(ref dense, ref propagator) => (dense, propagator),
+            _ => raise!(Unsupported, "only systems with the dense solver can be saved"),
+        };
+        let Dense { ref G, ref H, ref L, ref U, ref Z } = *dense;
+        let mut writer = Writer(writer);
+        writer.bytes(MAGIC)?;
+        writer.u32(VERSION)?;
+        writer.u64(checksum)?;
+        writer.f64(config.ambience)?;
+        writer.f64(config.time_step)?;
+        writer.usize(units)?;
+        writer.usize(nodes)?;
+        writer.usize(spots)?;
+        for names in &[unit_names, spot_names] {
+            writer.usize(names.len())?;
+            for name in names.iter() {
+                writer.usize(name.len())?;
+                writer.bytes(name.as_bytes())?;
+            }
+        }
+        writer.compressed(C)?;
+        writer.diagonal(D)?;
+        writer.compressed(Mq)?;
+        writer.conventional(G)?;
+        writer.conventional(H)?;
+        writer.diagonal(L)?;
+        writer.conventional(U)?;
+        writer.conventional(Z)?;
+        writer.conventional(&propagator.E)?;
+        writer.conventional(&propagator.F)?;
+        Ok(())
+    }
+
+    /// Read a system from a reader.
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<System> {
+        let mut reader = Reader(reader);
+        if &reader.bytes(MAGIC.len())?[..] != &MAGIC[..] {
+            raise!(Parse, "the file does not contain a system");
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            raise!(Unsupported, format!("the format version {} is not supported", version));
+        }
+        let checksum = reader.u64()?;
+        let config = Config {
+            ambience: reader.f64()?,
+            time_step: reader.f64()?,
+            solver: Solver::Dense,
+        };
+        let (units, nodes, spots) = (reader.usize()?, reader.usize()?, reader.usize()?);
+        let unit_names = reader.names(units)?;
+        let spot_names = reader.names(spots)?;
+        let C = reader.compressed((spots, nodes))?;
+        let D = reader.diagonal(nodes)?;
+        let Mq = reader.compressed((spots, nodes))?;
+        let G = reader.conventional((nodes, units))?;
+        let H = reader.conventional((spots, units))?;
+        let L = reader.diagonal(nodes)?;
+        let U = reader.conventional(nodes)?;
+        let Z = reader.conventional((nodes, units))?;
+        let E = reader.conventional(nodes)?;
+        let F = reader.conventional((nodes, units))?;
+        let propagator = Arc::new(Propagator { time_step: config.time_step, E: E, F: F });
+        Ok(System {
+            config: config,
+            units: units, nodes: nodes, spots: spots,
+            C: C, D: D, Mq: Mq,
+            unit_names: unit_names,
+            spot_names: spot_names,
+            checksum: checksum,
+            model: Model::Dense(Dense { G: G, H: H, L: L, U: U, Z: Z }, propagator),
+        })
+    }
+}
+
+impl Simulator {
+    /// Create a simulator from a system saved to a file.
+    pub fn load<T: AsRef<Path>>(path: T) -> Result<Simulator> {
+        Ok(Simulator::from(Arc::new(System::load(path)?)))
+    }
+
+    /// Create a simulator from a system saved to a file and check that the
+    /// system corresponds to a circuit.
+    pub fn load_for<T: AsRef<Path>>(path: T, circuit: &Circuit) -> Result<Simulator> {
+        Ok(Simulator::from(Arc::new(System::load_for(path, circuit)?)))
+    }
+}
+
+struct Reader<'l, R: 'l>(&'l mut R);
+
+struct Writer<'l, W: 'l>(&'l mut W);
+
+impl<'l, R: Read> Reader<'l, R> {
+    fn bytes(&mut self, count: usize) -> Result<Vec<u8>> {
+        let mut buffer = vec![];
+        ok!(self.0.by_ref().take(count as u64).read_to_end(&mut buffer), Io);
+        if buffer.len() != count {
+            raise!(Parse, "the file is truncated");
+        }
+        Ok(buffer)
+    }
+
+    fn names(&mut self, expected: usize) -> Result<Vec<String>> {
+        let count = self.usize()?;
+        if count != 0 && count != expected {
+            raise!(Parse, format!("the file contains {} names instead of {}", count, expected));
+        }
+        (0..count).map(|_| self.string()).collect()
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let length = self.usize()?;
+        match String::from_utf8(self.bytes(length)?) {
+            Ok(string) => Ok(string),
+            _ => raise!(Parse, "the file contains an invalid name"),
+        }
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut buffer = [0; 4];
+        self.fill(&mut buffer)?;
+        Ok(u32::from_le_bytes(buffer))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut buffer = [0; 8];
+        self.fill(&mut buffer)?;
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        let value = self.u64()?;
+        if value > usize::max_value() as u64 {
+            raise!(Parse, "the file contains a size that is too large");
+        }
+        Ok(value as usize)
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    fn f64s(&mut self, count: usize) -> Result<Vec<f64>> {
+        let mut values = Vec::with_capacity(count.min(1 << 16));
+        for _ in 0..count {
+            values.push(self.f64()?);
+        }
+        Ok(values)
+    }
+
+    fn usizes(&mut self, count: usize, bound: usize) -> Result<Vec<usize>> {
+        let mut values = Vec::with_capacity(count.min(1 << 16));
+        for _ in 0..count {
+            let value = self.usize()?;
+            if value >= bound {
+                raise!(Parse, "the file contains an index that is out of bounds");
+            }
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    fn conventional<S: Size>(&mut self, size: S) -> Result<Conventional<f64>> {
+        let (rows, columns) = self.dimensions(size)?;
+        let values = self.f64s(rows * columns)?;
+        Ok(Conventional::from_vec((rows, columns), values))
+    }
+
+    fn diagonal<S: Size>(&mut self, size: S) -> Result<Diagonal<f64>> {
+        let (rows, columns) = self.dimensions(size)?;
+        let values = self.f64s(rows.min(columns))?;
+        Ok(Diagonal::from_vec((rows, columns), values))
+    }
+
+    fn compressed<S: Size>(&mut self, size: S) -> Result<Compressed<f64>> {
+        let (rows, columns) = self.dimensions(size)?;
+        let nonzeros = self.usize()?;
+        if nonzeros > rows * columns {
+            raise!(Parse, "the file contains too many nonzero elements");
+        }
+        let (variant, major, minor) = match self.bytes(1)?[0] {
+            0 => (Variant::Column, columns, rows),
+            1 => (Variant::Row, rows, columns),
+            _ => raise!(Parse, "the file contains an unknown matrix variant"),
+        };
+        let values = self.f64s(nonzeros)?;
+        let indices = self.usizes(nonzeros, minor)?;
+        let offsets = self.usizes(major + 1, nonzeros + 1)?;
+        if offsets[0] != 0 || offsets[major] != nonzeros ||
+           offsets.windows(2).any(|pair| pair[0] > pair[1]) {
+            raise!(Parse, "the file contains inconsistent offsets");
+        }
+        Ok(Compressed {
+            rows: rows, columns: columns, nonzeros: nonzeros, variant: variant,
+            values: values, indices: indices, offsets: offsets,
+        })
+    }
+
+    fn dimensions<S: Size>(&mut self, size: S) -> Result<(usize, usize)> {
+        let expected = size.dimensions();
+        let dimensions = (self.usize()?, self.usize()?);
+        if dimensions.0.checked_mul(dimensions.1).is_none() {
+            raise!(Parse, "the file contains a matrix that is too large");
+        }
+        if dimensions != expected {
+            raise!(Parse, format!("the file contains a {} × {} matrix instead of a {} × {} one",
+                                  dimensions.0, dimensions.1, expected.0, expected.1));
+        }
+        Ok(dimensions)
+    }
+
+    fn fill(&mut self, buffer: &mut [u8]) -> Result<()> {
+        match self.0.read_exact(buffer) {
+            Ok(()) => Ok(()),
+            Err(ref error) if error.kind() == IoErrorKind::UnexpectedEof => {
+                raise!(Parse, "the file is truncated")
+            },
+            Err(error) => Err(::Error::wrap(::ErrorKind::Io, error)),
+        }
+    }
+}
+
+impl<'l, W: Write> Writer<'l, W> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        ok!(self.0.write_all(bytes), Io);
+        Ok(())
+    }
+
+    fn u32(&mut self, value: u32) -> Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn u64(&mut self, value: u64) -> Result<()> {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    fn usize(&mut self, value: usize) -> Result<()> {
+        self.u64(value as u64)
+    }
+
+    fn f64(&mut self, value: f64) -> Result<()> {
+        self.u64(value.to_bits())
+    }
+
+    fn f64s(&mut self, values: &[f64]) -> Result<()> {
+        for &value in values {
+            self.f64(value)?;
+        }
+        Ok(())
+    }
+
+    fn conventional(&mut self, matrix: &Conventional<f64>) -> Result<()> {
+        self.usize(matrix.rows)?;
+        self.usize(matrix.columns)?;
+        self.f64s(&matrix.values)
+    }
+
+    fn diagonal(&mut self, matrix: &Diagonal<f64>) -> Result<()> {
+        self.usize(matrix.rows)?;
+        self.usize(matrix.columns)?;
+        self.f64s(&matrix.values)
+    }
+
+    fn compressed(&mut self, matrix: &Compressed<f64>) -> Result<()> {
+        self.usize(matrix.rows)?;
+        self.usize(matrix.columns)?;
+        self.usize(matrix.nonzeros)?;
+        self.bytes(&[match matrix.variant { Variant::Column => 0, Variant::Row => 1 }])?;
+        self.f64s(&matrix.values)?;
+        for &i in matrix.indices.iter().chain(&matrix.offsets) {
+            self.usize(i)?;
+        }
+        Ok(())
+    }
+}
@@ -15,8 +15,9 @@ fn new() {
     assert_eq!(system.units, 2);
     assert_eq!(system.nodes, 4 * 2 + 12);
     assert_eq!(system.spots, 2);
-    assert::close(&system.E.values, &fixture::E[..], 1e-13);
-    assert::close(&system.F.values, &fixture::F[..], 1e-13);
+    let propagator = simulator.propagator.unwrap();
+    assert::close(&propagator.E.values, &fixture::E[..], 1e-13);
+    assert::close(&propagator.F.values, &fixture::F[..], 1e-13);
 }
 
 pub fn setup(name: &str) -> Simulator {
@@ -0,0 +1,110 @@
+#![allow(non_snake_case)]
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use Result;
+
+/// The HotSpot format.
+///
+/// A trace is a text file whose first line contains the names of the
+/// processing elements and each subsequent line contains one value per
+/// processing element. Power traces (`.ptrace`) contain power in Watts, and
+/// temperature traces (`.ttrace`) contain temperature. The values are separated
+/// by whitespace.
+pub struct HotSpot;
+
+impl HotSpot {
+    /// Read a power trace.
+    ///
+    /// The columns of the trace are matched with `units` by name. The result
+    /// contains `units.len()` values per time step and can be passed directly
+    /// to `Simulator::next`.
+    pub fn read<T: AsRef<Path>>(path: T, units: &[String]) -> Result<Vec<f64>> {
+        HotSpot::read_from(BufReader::new(ok!(File::open(path), Io)), units)
+    }
+
+    /// Read a power trace from a reader.
+    pub fn read_from<R: BufRead>(reader: R, units: &[String]) -> Result<Vec<f64>> {
+        let mut mapping: Option<Vec<usize>> = None;
+        let mut P = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = ok!(line, Io);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mapping = match mapping {
+                Some(ref mapping) => mapping,
+                _ => {
+                    mapping = Some(map(line, units)?);
+                    continue;
+                },
+            };
+            let values = line.split_whitespace().collect::<Vec<_>>();
+            if values.len() != mapping.len() {
+                raise!(Parse, format!("line {} should contain {} values", i + 1, mapping.len()));
+            }
+            let offset = P.len();
+            P.extend(units.iter().map(|_| 0.0));
+            for (&j, value) in mapping.iter().zip(values) {
+                P[offset + j] = match value.parse::<f64>() {
+                    Ok(value) => value,
+                    _ => raise!(Parse, format!("cannot parse “{}” on line {}", value, i + 1)),
+                };
+            }
+        }
+        if mapping.is_none() {
+            raise!(Parse, "the trace has no header");
+        }
+        Ok(P)
+    }
+
+    /// Write a temperature trace.
+    ///
+    /// `Q` is expected to have the layout produced by `Simulator::next`, that
+    /// is, `units.len()` values per time step. If `celsius` is set, the
+    /// temperature is converted from Kelvin to Celsius.
+    pub fn write<T: AsRef<Path>>(path: T, units: &[String], Q: &[f64],
+                                 celsius: bool) -> Result<()> {
+        let mut writer = BufWriter::new(ok!(File::create(path), Io));
+        HotSpot::write_to(&mut writer, units, Q, celsius)?;
+        ok!(writer.flush(), Io);
+        Ok(())
+    }
+
+    /// Write a temperature trace to a writer.
+    pub fn write_to<W: Write>(writer: &mut W, units: &[String], Q: &[f64],
+                              celsius: bool) -> Result<()> {
+        let count = units.len();
+        if count == 0 || Q.len() % count != 0 {
+            raise!(InvalidInput, format!("the number of values is not a multiple of {}", count));
+        }
+        let offset = if celsius { -273.15 } else { 0.0 };
+        ok!(writeln!(writer, "{}", units.join("\t")), Io);
+        for Q in Q.chunks(count) {
+            let line = Q.iter().map(|value| format!("{:.2}", value + offset)).collect::<Vec<_>>();
+            ok!(writeln!(writer, "{}", line.join("\t")), Io);
+        }
+        Ok(())
+    }
+}
+
+fn map(line: &str, units: &[String]) -> Result<Vec<usize>> {
+    let mut mapping = vec![];
+    for name in line.split_whitespace() {
+        let i = match units.iter().position(|unit| unit == name) {
+            Some(i) => i,
+            _ => raise!(Parse, format!("the processing element “{}” is unknown", name)),
+        };
+        if mapping.contains(&i) {
+            raise!(Parse, format!("the processing element “{}” is given twice", name));
+        }
+        mapping.push(i);
+    }
+    if let Some(unit) = units.iter().enumerate().find(|&(i, _)| !mapping.contains(&i)) {
+        raise!(Parse, format!("the processing element “{}” is missing", unit.1));
+    }
+    Ok(mapping)
+}
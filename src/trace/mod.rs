@@ -0,0 +1,5 @@
+//! Power and temperature traces.
+
+mod hotspot;
+
+pub use self::hotspot::HotSpot;
@@ -4,7 +4,7 @@ use assert;
 use matrix::format::{Conventional, Diagonal};
 use std::path::PathBuf;
 use temperature::circuit::ThreeDICE;
-use temperature::{Circuit, Config, Simulator};
+use temperature::{Circuit, Config, ErrorKind, Simulator};
 
 mod fixture;
 
@@ -61,6 +61,21 @@ fn distribution() {
     assert::close(&*distribution, &*identity, 1e-15);
 }
 
+#[test]
+fn error() {
+    let error = ThreeDICE::new("nonexistent.stk").err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Io);
+}
+
+#[test]
+fn names() {
+    let circuit = ThreeDICE::new(find("004.stk")).unwrap();
+    let names = vec!["Core0", "Core1", "Core2", "Core3"];
+    assert_eq!(circuit.units, names.iter().map(|name| name.to_string()).collect::<Vec<_>>());
+    assert_eq!(circuit.spots, circuit.units);
+    assert_eq!(circuit.unit("Core2"), Some(2));
+}
+
 #[test]
 fn next() {
     let mut simulator = setup("004.stk");
@@ -69,6 +84,16 @@ fn next() {
     assert::close(&Q, &fixture::Q[..], 5.0);
 }
 
+#[test]
+fn steady() {
+    let simulator = setup("004.stk");
+    let mut Q = vec![0.0; UNITS];
+    simulator.steady(&[10.0, 20.0, 30.0, 40.0], &mut Q).unwrap();
+    assert::close(&Q, &vec![
+        3.556272578548002e+02, 3.856526078345531e+02, 4.156779578143060e+02, 4.457033077940591e+02,
+    ], 1e-8);
+}
+
 fn setup(name: &str) -> Simulator {
     let circuit = ThreeDICE::new(find(name)).unwrap();
     Simulator::new(circuit, Config::default()).unwrap()
@@ -1,13 +1,306 @@
 #![allow(non_snake_case)]
 
 use assert;
+use matrix::c64;
 use std::path::PathBuf;
 use temperature::circuit::HotSpot;
-use temperature::{Config, Simulator};
+use temperature::control::{PID, Threshold};
+use temperature::leakage::{Exponential, Linear};
+use temperature::network::{Cauer, Foster, Stage};
+use temperature::trace;
+use temperature::{Config, ErrorKind, Reduction, Simulator, Solver, System};
 
 mod fixture;
 
 const UNITS: usize = 2;
+const NODES: usize = 4 * 2 + 12;
+
+#[test]
+fn binary() {
+    use std::process::Command;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_temperature"))
+                         .arg("simulate")
+                         .arg("--hotspot").arg(find("002.flp")).arg(find("hotspot.config"))
+                         .arg("--power").arg(find("002.ptrace"))
+                         .output().unwrap();
+    assert!(output.status.success());
+    let units = vec!["core0".to_string(), "core1".to_string()];
+    let Q1 = trace::HotSpot::read_from(&output.stdout[..], &units).unwrap();
+    let mut simulator = setup("002");
+    let mut Q2 = vec![0.0; 2 * UNITS];
+    simulator.next(&[2.0, 1.0, 0.0, 3.5], &mut Q2);
+    assert::close(&Q1, &Q2, 5e-3);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_temperature"))
+                         .arg("simulate").arg("--power").arg(find("002.ptrace"))
+                         .output().unwrap();
+    assert!(!output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_temperature"))
+                         .arg("simulate")
+                         .arg("--hotspot").arg(find("002.flp")).arg(find("hotspot.config"))
+                         .arg("--power").arg(find("002.ptrace"))
+                         .arg("--time-step").arg("0")
+                         .output().unwrap();
+    assert!(!output.status.success());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_temperature"))
+                         .arg("simulate")
+                         .arg("--hotspot").arg(find("002.flp")).arg(find("hotspot.config"))
+                         .arg("--power").arg(find("002.ptrace"))
+                         .arg("--celcius")
+                         .output().unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn control() {
+    let ambience = Config::default().ambience;
+    let (lower, upper) = (ambience + 1.0, ambience + 2.0);
+    let mut simulator = setup("002");
+    let levels = vec![vec![20.0, 20.0], vec![10.0, 10.0], vec![0.0, 0.0]];
+    let mut controller = Threshold::new(levels, lower, upper);
+    let log = simulator.control(&mut controller, 440);
+    assert_eq!(log.power.len(), 440 * UNITS);
+    assert_eq!(log.temperature.len(), 440 * UNITS);
+    for i in 1..440 {
+        let Q = &log.temperature[((i - 1) * UNITS)..(i * UNITS)];
+        let (previous, current) = (log.power[(i - 1) * UNITS], log.power[i * UNITS]);
+        if Q.iter().any(|&value| value > upper) {
+            assert!(current <= previous);
+        }
+        if Q.iter().all(|&value| value < lower) {
+            assert!(current >= previous);
+        }
+    }
+
+    let nominal = vec![10.0, 20.0];
+    let mut simulator1 = setup("002");
+    let mut simulator2 = setup("002");
+    let mut controller = PID::new(nominal.clone(), 1000.0, 1.0, 1.0, 0.0, 1e-3);
+    let log = simulator1.control(&mut controller, 100);
+    let P = nominal.iter().cycle().take(100 * UNITS).cloned().collect::<Vec<_>>();
+    let mut Q = vec![0.0; 100 * UNITS];
+    simulator2.next(&P, &mut Q);
+    assert::close(&log.temperature, &Q, 1e-10);
+
+    let mut simulator = setup("002");
+    let mut controller = PID::new(nominal.clone(), ambience, 1.0, 1.0, 0.0, 1e-3);
+    let log = simulator.control(&mut controller, 100);
+    assert!(log.power[(99 * UNITS)..].iter().zip(&nominal).all(|(&one, &two)| one < two));
+}
+
+#[test]
+fn error() {
+    use std::error::Error;
+
+    let error = HotSpot::new("nonexistent.flp", find("hotspot.config")).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Io);
+    assert!(error.source().is_some());
+    assert!(error.to_string().contains("nonexistent.flp"));
+}
+
+#[test]
+fn evaluate() {
+    let mut simulator = setup("002");
+    let mut Q = vec![0.0; 220 * UNITS];
+    simulator.next(&fixture::P[..(220 * UNITS)], &mut Q);
+    let P = vec![10.0, 20.0];
+    let times = vec![0.0, 5e-4, 1.5e-3, 2e-3];
+    let mut Q1 = vec![0.0; 4 * UNITS];
+    simulator.evaluate(&P, &times, &mut Q1).unwrap();
+    assert::close(&Q1[..UNITS], &Q[(219 * UNITS)..], 1e-10);
+    let mut Q2 = vec![0.0; 2 * UNITS];
+    simulator.set_time_step(5e-4);
+    simulator.next(&P, &mut Q2[..UNITS]);
+    simulator.set_time_step(1e-3);
+    simulator.next(&P, &mut Q2[UNITS..]);
+    assert::close(&Q1[UNITS..(3 * UNITS)], &Q2, 1e-10);
+    let mut Q3 = vec![0.0; UNITS];
+    simulator.set_time_step(5e-4);
+    simulator.next(&P, &mut Q3);
+    assert::close(&Q1[(3 * UNITS)..], &Q3, 1e-10);
+}
+
+#[test]
+fn impedance() {
+    let mut simulator = setup("002");
+    let times = vec![1e-3, 1e-2, 0.1, 1.0];
+    let mut Z = vec![0.0; UNITS * UNITS * times.len()];
+    simulator.impedance(&times, &mut Z).unwrap();
+    let P = (0..1000).flat_map(|_| vec![1.0, 0.0]).collect::<Vec<_>>();
+    let mut Q = vec![0.0; 1000 * UNITS];
+    simulator.next(&P, &mut Q);
+    let ambience = Config::default().ambience;
+    let (mut expected, mut actual) = (vec![], vec![]);
+    for (i, &steps) in [1, 10, 100, 1000].iter().enumerate() {
+        for k in 0..UNITS {
+            expected.push(Q[(steps - 1) * UNITS + k] - ambience);
+            actual.push(Z[i * UNITS * UNITS + k]);
+        }
+    }
+    assert::close(&actual, &expected, 1e-10);
+
+    let mut actual = vec![0.0; Z.len()];
+    for j in 0..UNITS {
+        for k in 0..UNITS {
+            let foster = simulator.foster(j, k).unwrap();
+            for (i, &time) in times.iter().enumerate() {
+                actual[(i * UNITS + j) * UNITS + k] = foster.impedance(time);
+            }
+        }
+    }
+    assert::close(&actual, &Z, 1e-10);
+
+    let cauer = simulator.foster(0, 0).unwrap().cauer().unwrap();
+    let foster = cauer.foster().unwrap();
+    let actual = times.iter().map(|&time| foster.impedance(time)).collect::<Vec<_>>();
+    let expected = (0..times.len()).map(|i| Z[i * UNITS * UNITS]).collect::<Vec<_>>();
+    assert::close(&actual, &expected, 1e-8);
+    assert!(cauer.to_string().starts_with("stage\tresistance\tcapacitance\ttime constant\n1\t"));
+
+    let error = simulator.foster(UNITS, 0).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    let config = Config { solver: Solver::Sparse { tolerance: 1e-10 }, ..Config::default() };
+    let simulator = setup_with("002", config);
+    let error = simulator.foster(0, 0).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn invalid() {
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    assert!(circuit.validate().is_ok());
+    let mut invalid = circuit.clone();
+    invalid.capacitance[0] = -1.0;
+    let error = Simulator::new(invalid, Config::default()).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidCircuit);
+    let mut invalid = circuit.clone();
+    let value = invalid.conductance.get((0, 1));
+    invalid.conductance.set((0, 1), 2.0 * value - 1.0);
+    assert_eq!(invalid.validate().err().unwrap().kind(), ErrorKind::InvalidCircuit);
+    let mut invalid = circuit.clone();
+    invalid.units.pop();
+    assert_eq!(invalid.validate().err().unwrap().kind(), ErrorKind::InvalidCircuit);
+    let mut invalid = circuit.clone();
+    invalid.spots[1] = invalid.spots[0].clone();
+    assert_eq!(invalid.validate().err().unwrap().kind(), ErrorKind::InvalidCircuit);
+}
+
+#[test]
+fn modal() {
+    let config = Config { solver: Solver::Modal, ..Config::default() };
+    let mut simulator = setup_with("002", config);
+    let mut dense = setup("002");
+    let (mut Q1, mut Q2) = (vec![0.0; 440 * UNITS], vec![0.0; 440 * UNITS]);
+    simulator.next(&fixture::P, &mut Q1);
+    dense.next(&fixture::P, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-10);
+    assert::close(&simulator.temperature(), &dense.temperature(), 1e-10);
+
+    let T = (0..NODES).map(|i| 320.0 + i as f64).collect::<Vec<_>>();
+    simulator.set_temperature(&T);
+    assert::close(&simulator.temperature(), &T, 1e-10);
+    dense.set_temperature(&T);
+    simulator.set_ambience(&[300.0]);
+    dense.set_ambience(&[300.0]);
+    assert::close(&simulator.temperature(), &T, 1e-10);
+
+    let P = vec![10.0, 20.0, 30.0, 0.0];
+    let durations = vec![1e-3, 2.5e-3];
+    let (mut Q1, mut Q2) = (vec![0.0; 2 * UNITS], vec![0.0; 2 * UNITS]);
+    simulator.next_variable(&P, &durations, &mut Q1);
+    dense.next_variable(&P, &durations, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-10);
+
+    simulator.settle(&P[..UNITS]).unwrap();
+    dense.settle(&P[..UNITS]).unwrap();
+    assert::close(&simulator.temperature(), &dense.temperature(), 1e-10);
+}
+
+#[test]
+fn modes() {
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let modes = circuit.modes().unwrap();
+    assert_eq!(modes.len(), NODES);
+    for pair in modes.windows(2) {
+        assert!(pair[0].contribution >= pair[1].contribution);
+    }
+    for mode in &modes {
+        assert!(mode.eigenvalue < 0.0);
+        assert_eq!(mode.time_constant, -1.0 / mode.eigenvalue);
+        assert_eq!((mode.units.len(), mode.spots.len()), (UNITS, UNITS));
+    }
+    let simulator = setup("002");
+    let ambience = Config::default().ambience;
+    for j in 0..UNITS {
+        let mut P = vec![0.0; UNITS];
+        P[j] = 1.0;
+        let mut Q = vec![0.0; UNITS];
+        simulator.steady(&P, &mut Q).unwrap();
+        let expected = Q.iter().map(|&value| value - ambience).collect::<Vec<_>>();
+        let actual = (0..UNITS).map(|k| {
+            modes.iter().fold(0.0, |sum, mode| {
+                sum + mode.time_constant * mode.spots[k] * mode.units[j]
+            })
+        }).collect::<Vec<_>>();
+        assert::close(&actual, &expected, 1e-10);
+    }
+}
+
+#[test]
+fn names() {
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    assert_eq!(circuit.units, vec!["core0".to_string(), "core1".to_string()]);
+    assert_eq!(circuit.spots, circuit.units);
+    assert_eq!(circuit.unit("core1"), Some(1));
+    assert_eq!(circuit.spot("core2"), None);
+    let system = System::new(circuit, Config::default()).unwrap();
+    assert_eq!(system.units(), &["core0".to_string(), "core1".to_string()]);
+    assert_eq!(system.spot("core0"), Some(0));
+
+    let mut circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    circuit.units.clear();
+    circuit.spots.clear();
+    assert!(circuit.validate().is_ok());
+    let system = System::new(circuit, Config::default()).unwrap();
+    assert!(system.units().is_empty() && system.spots().is_empty());
+    assert_eq!(system.unit("core0"), None);
+    let mut bytes = vec![];
+    system.write_to(&mut bytes).unwrap();
+    assert!(System::read_from(&mut &bytes[..]).unwrap().spots().is_empty());
+
+    let circuit = HotSpot::new(find("032.flp"), find("hotspot.config")).unwrap();
+    assert_eq!(circuit.units.len(), 32);
+}
+
+#[test]
+fn network() {
+    let five = 5f64.sqrt();
+    let (slow, fast) = ((3.0 + five) / 2.0, (3.0 - five) / 2.0);
+    let (one, other) = ((5.0 + five) / 10.0 * slow, (5.0 - five) / 10.0 * fast);
+    let foster = Foster {
+        stages: vec![
+            Stage { resistance: other, capacitance: fast / other },
+            Stage { resistance: one, capacitance: slow / one },
+        ],
+    };
+    let values = vec![foster.impedance(0.0), foster.impedance(1e6)];
+    assert::close(&values, &vec![0.0, 2.0], 1e-12);
+    let cauer = foster.cauer().unwrap();
+    let values = cauer.stages.iter().flat_map(|stage| vec![stage.resistance, stage.capacitance]);
+    assert::close(&values.collect::<Vec<_>>(), &vec![1.0; 4], 1e-12);
+    let values = cauer.foster().unwrap().stages.iter().flat_map(|stage| {
+        vec![stage.resistance, stage.capacitance]
+    }).collect::<Vec<_>>();
+    assert::close(&values, &vec![other, fast / other, one, slow / one], 1e-12);
+
+    let foster = Foster { stages: vec![Stage { resistance: -1.0, capacitance: 1.0 }] };
+    assert_eq!(foster.cauer().err().unwrap().kind(), ErrorKind::InvalidInput);
+    let cauer = Cauer { stages: vec![Stage { resistance: 1.0, capacitance: 0.0 }] };
+    assert_eq!(cauer.foster().err().unwrap().kind(), ErrorKind::InvalidInput);
+}
 
 #[test]
 fn next_0() {
@@ -57,9 +350,423 @@ fn next_440() {
     assert::close(&Q, &fixture::Q[..], 0.1);
 }
 
+#[test]
+fn next_ambient() {
+    let ambience = Config::default().ambience;
+    let mut simulator = setup("002");
+    let mut Q = vec![0.0; 220 * UNITS];
+    simulator.set_ambience(&[ambience + 10.0]);
+    assert::close(&simulator.temperature(), &vec![ambience; NODES], 1e-10);
+    simulator.reset();
+    simulator.next_ambient(&fixture::P[..(220 * UNITS)], &vec![ambience + 10.0; 220], &mut Q);
+    let expected = fixture::Q[..(220 * UNITS)].iter().map(|&value| value + 10.0);
+    let expected = expected.collect::<Vec<_>>();
+    assert::close(&Q, &expected, 0.1);
+    let mut simulator = setup("002");
+    simulator.next_ambient(&fixture::P[..(220 * UNITS)], &vec![ambience; 220 * NODES], &mut Q);
+    assert::close(&Q, &fixture::Q[..(220 * UNITS)], 0.1);
+    let Tamb = (0..NODES).map(|i| ambience + i as f64).collect::<Vec<_>>();
+    simulator.set_ambience(&Tamb);
+    let average = ambience + (NODES - 1) as f64 / 2.0;
+    assert::close(&[simulator.config().ambience], &[average], 1e-10);
+}
+
+#[test]
+fn next_leaky() {
+    let ambience = Config::default().ambience;
+    let mut simulator1 = setup("002");
+    let mut simulator2 = setup("002");
+    let mut Q1 = vec![0.0; 440 * UNITS];
+    let mut Q2 = vec![0.0; 440 * UNITS];
+    let leakage = Linear { nominal: vec![0.0; UNITS], reference: ambience, coefficient: 1.0 };
+    simulator1.next_leaky(&fixture::P, &leakage, 1000.0, &mut Q1).unwrap();
+    assert::close(&Q1, &fixture::Q[..], 0.1);
+    let leakage = Exponential {
+        nominal: vec![1.0; UNITS],
+        reference: ambience,
+        coefficient: 0.01,
+    };
+    simulator2.next_leaky(&fixture::P, &leakage, 1000.0, &mut Q2).unwrap();
+    assert!(Q1.iter().zip(&Q2).all(|(&one, &two)| one < two));
+    let mut simulator = setup("002");
+    let leakage = Exponential { nominal: vec![1e3; UNITS], reference: ambience, coefficient: 1.0 };
+    assert!(simulator.next_leaky(&fixture::P, &leakage, 1000.0, &mut Q1).is_err());
+}
+
+#[test]
+fn next_variable() {
+    let mut simulator1 = setup("002");
+    let mut simulator2 = setup("002");
+    let durations = (0..220).map(|i| {
+        if i == 42 { 3e-3 } else { [1e-3, 2e-3, 5e-4][i % 3] }
+    }).collect::<Vec<_>>();
+    let mut Q1 = vec![0.0; 220 * UNITS];
+    let mut Q2 = vec![0.0; 220 * UNITS];
+    simulator1.next_variable(&fixture::P[..(220 * UNITS)], &durations, &mut Q1);
+    for i in 0..220 {
+        let range = (i * UNITS)..((i + 1) * UNITS);
+        simulator2.set_time_step(durations[i]);
+        simulator2.next(&fixture::P[range.clone()], &mut Q2[range]);
+    }
+    assert::close(&Q1, &Q2, 1e-10);
+    assert_eq!(simulator1.steps(), 220);
+}
+
+#[test]
+fn reduced() {
+    let mut dense = setup("002");
+    let mut expected = vec![0.0; 440 * UNITS];
+    dense.next(&fixture::P, &mut expected);
+    let maximum = fixture::P.iter().fold(0.0, |maximum: f64, &value| maximum.max(value.abs()));
+
+    let config = Config { solver: Solver::Reduced(Reduction::Modes(NODES)), ..Config::default() };
+    let mut simulator = setup_with("002", config);
+    assert_eq!(simulator.system().order(), NODES);
+    assert_eq!(simulator.system().bound(), 0.0);
+    let mut Q = vec![0.0; 440 * UNITS];
+    simulator.next(&fixture::P, &mut Q);
+    assert::close(&Q, &expected, 1e-10);
+
+    let reduction = Reduction::Tolerance(0.1);
+    let config = Config { solver: Solver::Reduced(reduction), ..Config::default() };
+    let mut simulator = setup_with("002", config);
+    let (order, bound) = (simulator.system().order(), simulator.system().bound());
+    assert!(order < NODES && bound <= 0.1);
+    simulator.next(&fixture::P, &mut Q);
+    assert::close(&Q, &expected, bound * maximum);
+    let P = vec![10.0, 20.0];
+    let (mut Q1, mut Q2) = (vec![0.0; UNITS], vec![0.0; UNITS]);
+    simulator.reset();
+    simulator.settle(&P).unwrap();
+    simulator.next(&P, &mut Q1);
+    dense.steady(&P, &mut Q2).unwrap();
+    assert::close(&Q1, &Q2, 1e-10);
+    let time_step = simulator.config().time_step;
+    simulator.reset();
+    simulator.evaluate(&P, &[time_step], &mut Q1).unwrap();
+    simulator.next(&P, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-12);
+
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let config = Config { solver: Solver::Reduced(Reduction::Modes(0)), ..Config::default() };
+    let error = Simulator::new(circuit, config).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn reset() {
+    let mut simulator = setup("002");
+    let mut Q = vec![0.0; 220 * UNITS];
+    simulator.next(&fixture::P[..(220 * UNITS)], &mut Q);
+    simulator.reset();
+    assert::close(&simulator.temperature(), &vec![Config::default().ambience; NODES], 0.0);
+    simulator.next(&fixture::P[..(220 * UNITS)], &mut Q);
+    assert::close(&Q, &fixture::Q[..(220 * UNITS)], 0.1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialization() {
+    use serde_json;
+    use temperature::Circuit;
+
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let config = Config { time_step: 2e-3, ..Config::default() };
+    let json = serde_json::to_string(&(&circuit, &config)).unwrap();
+    let (other, config): (Circuit, Config) = serde_json::from_str(&json).unwrap();
+    assert_eq!(config.time_step, 2e-3);
+    assert_eq!(other.units, circuit.units);
+    assert_eq!(other.conductance, circuit.conductance);
+
+    let P = vec![10.0, 20.0, 30.0, 0.0];
+    let (mut Q1, mut Q2) = (vec![0.0; 2 * UNITS], vec![0.0; 2 * UNITS]);
+    Simulator::new(circuit, config).unwrap().next(&P, &mut Q1);
+    Simulator::new(other, config).unwrap().next(&P, &mut Q2);
+    assert::close(&Q1, &Q2, 0.0);
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value[0].as_object_mut().unwrap().remove("units");
+    value[0].as_object_mut().unwrap().remove("spots");
+    let other: Circuit = serde_json::from_value(value[0].take()).unwrap();
+    assert!(other.units.is_empty() && other.spots.is_empty());
+
+    let json = json.replacen("\"offsets\":[0,", "\"offsets\":[1,", 1);
+    assert!(serde_json::from_str::<(Circuit, Config)>(&json).is_err());
+}
+
+#[test]
+fn settle() {
+    let mut simulator = setup("002");
+    let P = vec![10.0, 20.0];
+    let (mut Q1, mut Q2) = (vec![0.0; UNITS], vec![0.0; UNITS]);
+    simulator.steady(&P, &mut Q1).unwrap();
+    simulator.settle(&P).unwrap();
+    simulator.next(&P, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-8);
+}
+
+#[test]
+fn snapshot() {
+    let mut simulator = setup("002");
+    let mut Q1 = vec![0.0; 220 * UNITS];
+    let mut Q2 = vec![0.0; 220 * UNITS];
+    let mut Q3 = vec![0.0; 220 * UNITS];
+    simulator.next(&fixture::P[..(220 * UNITS)], &mut Q1);
+    let snapshot = simulator.snapshot();
+    assert_eq!(snapshot.steps(), 220);
+    simulator.next(&fixture::P[(220 * UNITS)..], &mut Q1);
+    assert_eq!(simulator.steps(), 440);
+    let mut fork = simulator.fork(&snapshot);
+    simulator.restore(&snapshot);
+    assert_eq!(simulator.steps(), 220);
+    simulator.next(&fixture::P[(220 * UNITS)..], &mut Q2);
+    fork.next(&fixture::P[(220 * UNITS)..], &mut Q3);
+    assert::close(&Q1, &Q2, 1e-12);
+    assert::close(&Q1, &Q3, 1e-12);
+
+    let ambience = Config::default().ambience;
+    let snapshot = simulator.snapshot();
+    simulator.set_ambience(&[300.0]);
+    assert_eq!(simulator.config().ambience, 300.0);
+    let fork = simulator.fork(&snapshot);
+    simulator.restore(&snapshot);
+    for simulator in &[&simulator, &fork] {
+        assert_eq!(simulator.config().ambience, ambience);
+        assert::close(simulator.ambience(), &vec![ambience; NODES][..], 0.0);
+    }
+}
+
+#[test]
+fn sparse() {
+    let config = Config { solver: Solver::Sparse { tolerance: 1e-10 }, ..Config::default() };
+    let mut simulator = setup_with("002", config);
+    let mut Q = vec![0.0; 440 * UNITS];
+    simulator.next(&fixture::P, &mut Q);
+    assert::close(&Q, &fixture::Q[..], 0.1);
+    let mut dense = setup("002");
+    let mut expected = vec![0.0; 440 * UNITS];
+    dense.next(&fixture::P, &mut expected);
+    assert::close(&Q, &expected, 1e-6);
+
+    let P = vec![10.0, 20.0, 30.0, 0.0];
+    let (mut Q1, mut Q2) = (vec![0.0; 2 * UNITS], vec![0.0; 2 * UNITS]);
+    simulator.steady(&P, &mut Q1).unwrap();
+    dense.steady(&P, &mut Q2).unwrap();
+    assert::close(&Q1, &Q2, 1e-6);
+
+    let durations = vec![1e-3, 2.5e-3];
+    let (mut Q1, mut Q2) = (vec![0.0; 2 * UNITS], vec![0.0; 2 * UNITS]);
+    simulator.next_variable(&P, &durations, &mut Q1);
+    dense.next_variable(&P, &durations, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-6);
+
+    let error = simulator.system().write_to(&mut vec![]).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let config = Config { solver: Solver::Sparse { tolerance: 0.0 }, ..Config::default() };
+    let error = Simulator::new(circuit, config).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn steady() {
+    let config = Config { time_step: 1e6, ..Config::default() };
+    let mut simulator = setup_with("002", config);
+    let P = vec![10.0, 20.0, 30.0, 0.0];
+    let mut Q1 = vec![0.0; 2 * UNITS];
+    let mut Q2 = vec![0.0; 2 * UNITS];
+    simulator.steady(&P, &mut Q1).unwrap();
+    simulator.next(&P, &mut Q2);
+    assert::close(&Q1, &Q2, 1e-10);
+}
+
+#[test]
+fn storage() {
+    use std::sync::Arc;
+    use std::{env, fs, process};
+
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let checksum = circuit.checksum();
+    let system = System::new(circuit.clone(), Config::default()).unwrap();
+    assert_eq!(system.checksum(), checksum);
+    let path = env::temp_dir().join(format!("temperature-{}.system", process::id()));
+    system.save(&path).unwrap();
+    assert!(Simulator::load_for(&path, &circuit).is_ok());
+    let mut other = circuit.clone();
+    other.capacitance[0] *= 2.0;
+    let error = System::load_for(&path, &other).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
+    let other = System::load(&path).unwrap();
+    assert_eq!(other.checksum(), checksum);
+    assert_eq!(other.units(), system.units());
+    let P = vec![10.0, 20.0, 30.0, 0.0];
+    let (mut Q1, mut Q2) = (vec![0.0; 2 * UNITS], vec![0.0; 2 * UNITS]);
+    Simulator::from(Arc::new(system)).next(&P, &mut Q1);
+    Simulator::load(&path).unwrap().next(&P, &mut Q2);
+    assert::close(&Q1, &Q2, 0.0);
+
+    let mut bytes = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(System::read_from(&mut &bytes[..(bytes.len() - 1)]).is_err());
+    bytes[0] = b'X';
+    let error = System::read_from(&mut &bytes[..]).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Parse);
+}
+
+#[test]
+fn system() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let circuit = HotSpot::new(find("002.flp"), find("hotspot.config")).unwrap();
+    let system = Arc::new(System::new(circuit, Config::default()).unwrap());
+    let handles = (0..4).map(|_| {
+        let system = system.clone();
+        thread::spawn(move || {
+            let mut simulator = Simulator::from(system);
+            let mut Q = vec![0.0; 440 * UNITS];
+            simulator.next(&fixture::P, &mut Q);
+            Q
+        })
+    }).collect::<Vec<_>>();
+    for handle in handles {
+        assert::close(&handle.join().unwrap(), &fixture::Q[..], 0.1);
+    }
+}
+
+#[test]
+fn temperature() {
+    let mut simulator1 = setup("002");
+    let mut simulator2 = setup("002");
+    let mut Q1 = vec![0.0; 220 * UNITS];
+    let mut Q2 = vec![0.0; 220 * UNITS];
+    simulator1.next(&fixture::P[..(220 * UNITS)], &mut Q1);
+    simulator2.set_temperature(&simulator1.temperature());
+    simulator1.next(&fixture::P[(220 * UNITS)..], &mut Q1);
+    simulator2.next(&fixture::P[(220 * UNITS)..], &mut Q2);
+    assert::close(&Q1, &Q2, 1e-10);
+}
+
+#[test]
+fn time_step() {
+    let config = Config { time_step: 2e-3, ..Config::default() };
+    let mut simulator1 = setup_with("002", config);
+    let mut simulator2 = setup("002");
+    let mut Q1 = vec![0.0; 220 * UNITS];
+    let mut Q2 = vec![0.0; 220 * UNITS];
+    simulator2.set_time_step(2e-3);
+    assert_eq!(simulator2.config().time_step, 2e-3);
+    simulator1.next(&fixture::P[..(220 * UNITS)], &mut Q1);
+    simulator2.next(&fixture::P[..(220 * UNITS)], &mut Q2);
+    assert::close(&Q1, &Q2, 1e-10);
+    simulator2.reset();
+    simulator2.set_time_step(1e-3);
+    simulator2.next(&fixture::P[..(220 * UNITS)], &mut Q2);
+    assert::close(&Q2, &fixture::Q[..(220 * UNITS)], 0.1);
+}
+
+#[test]
+fn trace() {
+    let units = HotSpot::units(find("002.flp")).unwrap();
+    assert_eq!(units, vec!["core0".to_string(), "core1".to_string()]);
+    let P = trace::HotSpot::read(find("002.ptrace"), &units).unwrap();
+    assert_eq!(P, vec![2.0, 1.0, 0.0, 3.5]);
+
+    let units = vec!["core0".to_string()];
+    let error = trace::HotSpot::read(find("002.ptrace"), &units).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Parse);
+    let units = vec!["core0".to_string(), "core1".to_string(), "core2".to_string()];
+    let error = trace::HotSpot::read(find("002.ptrace"), &units).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Parse);
+    let error = trace::HotSpot::read_from("core0 core1\n1.0\n".as_bytes(), &units[..2]);
+    assert_eq!(error.err().unwrap().kind(), ErrorKind::Parse);
+
+    let units = &units[..2];
+    let mut buffer = vec![];
+    let Q = vec![318.15, 319.16, 320.0, 321.5];
+    trace::HotSpot::write_to(&mut buffer, units, &Q, true).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), "core0\tcore1\n45.00\t46.01\n46.85\t48.35\n");
+    let mut buffer = vec![];
+    trace::HotSpot::write_to(&mut buffer, units, &Q, false).unwrap();
+    assert_eq!(trace::HotSpot::read_from(&buffer[..], units).unwrap(), Q);
+    let error = trace::HotSpot::write_to(&mut vec![], units, &Q[..3], false).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn transfer() {
+    let simulator = setup("002");
+    let frequencies = vec![0.0, 1.0, 1e2, 1e4];
+    let count = UNITS * UNITS * frequencies.len();
+    let mut H = vec![c64::new(0.0, 0.0); count];
+    simulator.transfer(&frequencies, &mut H).unwrap();
+    let (mut Q, ambience) = (vec![0.0; UNITS * UNITS], Config::default().ambience);
+    simulator.steady(&[1.0, 0.0, 0.0, 1.0], &mut Q).unwrap();
+    let expected = Q.iter().map(|&value| value - ambience).collect::<Vec<_>>();
+    let actual = H[..(UNITS * UNITS)].iter().map(|value| value.re).collect::<Vec<_>>();
+    assert::close(&actual, &expected, 1e-10);
+
+    let mut expected = vec![c64::new(0.0, 0.0); count];
+    for j in 0..UNITS {
+        for k in 0..UNITS {
+            let foster = simulator.foster(j, k).unwrap();
+            for (f, &frequency) in frequencies.iter().enumerate() {
+                let omega = 2.0 * ::std::f64::consts::PI * frequency;
+                expected[(f * UNITS + j) * UNITS + k] = foster.stages.iter().map(|stage| {
+                    stage.resistance / c64::new(1.0, omega * stage.time_constant())
+                }).fold(c64::new(0.0, 0.0), |sum, value| sum + value);
+            }
+        }
+    }
+    let parts = |H: &[c64]| {
+        H.iter().flat_map(|value| vec![value.re, value.im]).collect::<Vec<_>>()
+    };
+    assert::close(&parts(&H), &parts(&expected), 1e-10);
+
+    let (mut magnitude, mut phase) = (vec![0.0; count], vec![0.0; count]);
+    simulator.bode(&frequencies, &mut magnitude, &mut phase).unwrap();
+    let expected = H.iter().map(|value| 20.0 * value.norm().log10()).collect::<Vec<_>>();
+    assert::close(&magnitude, &expected, 1e-10);
+    for f in 0..frequencies.len() {
+        let i = f * UNITS * UNITS;
+        assert!(phase[i] <= 0.0 && phase[i] > -90.0);
+        if f > 0 {
+            assert!(magnitude[i] < magnitude[i - UNITS * UNITS]);
+        }
+    }
+
+    let config = Config { solver: Solver::Sparse { tolerance: 1e-10 }, ..Config::default() };
+    let simulator = setup_with("002", config);
+    let error = simulator.transfer(&frequencies, &mut H).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::Unsupported);
+}
+
+#[test]
+fn try_next() {
+    let mut simulator = setup("002");
+    let mut Q = vec![0.0; 440 * UNITS];
+    let error = simulator.try_next(&fixture::P[..3], &mut Q[..2]).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    let error = simulator.try_next(&fixture::P[..4], &mut Q[..2]).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    let mut P = fixture::P.to_vec();
+    P[3 * UNITS + 1] = ::std::f64::NAN;
+    let error = simulator.try_next(&P, &mut Q).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("processing element 1 at time step 3"));
+    assert_eq!(simulator.steps(), 0);
+    simulator.try_next(&fixture::P, &mut Q).unwrap();
+    assert::close(&Q, &fixture::Q[..], 0.1);
+}
+
 fn setup(name: &str) -> Simulator {
+    setup_with(name, Config::default())
+}
+
+fn setup_with(name: &str, config: Config) -> Simulator {
     let circuit = HotSpot::new(find(&format!("{}.flp", name)), find("hotspot.config")).unwrap();
-    Simulator::new(circuit, Config::default()).unwrap()
+    Simulator::new(circuit, config).unwrap()
 }
 
 fn find(name: &str) -> PathBuf {
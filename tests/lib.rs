@@ -1,5 +1,7 @@
 extern crate assert;
 extern crate matrix;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate temperature;
 
 #[cfg(feature = "hotspot")]